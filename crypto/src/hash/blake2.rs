@@ -0,0 +1,314 @@
+//! The BLAKE2 Cryptographic Hash and Message Authentication Code (MAC)
+//! https://www.rfc-editor.org/rfc/rfc7693
+//!
+//! BLAKE2b works on 64 bit words and is optimized for 64 bit platforms, BLAKE2s works on 32 bit
+//! words. Apart from the word size, constants and number of rounds they share the same algorithm,
+//! so it's implemented once, generic over the word.
+//! Unlike sha1 and md4, a key can be passed directly: it's processed as a first, separate block
+//! and the final block is flagged, so there's no need for HMAC and no length extension.
+
+use std::convert::TryInto;
+use std::ops::BitXor;
+
+/// Message word schedule permutations for each round.
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Everything that differs between BLAKE2b and BLAKE2s.
+trait Word: Copy + BitXor<Output = Self> + Default {
+    const BYTES: usize;
+    const ROUNDS: usize;
+    /// Rotation constants (R1, R2, R3, R4) of the G mixing function.
+    const ROTATIONS: [u32; 4];
+    const IV: [Self; 8];
+    fn from_le_slice(bytes: &[u8]) -> Self;
+    fn to_le_vec(self) -> Vec<u8>;
+    fn from_u64(x: u64) -> Self;
+    fn wrapping_add(self, other: Self) -> Self;
+    fn rotate_right(self, n: u32) -> Self;
+    fn not(self) -> Self;
+}
+
+impl Word for u64 {
+    const BYTES: usize = 8;
+    const ROUNDS: usize = 12;
+    const ROTATIONS: [u32; 4] = [32, 24, 16, 63];
+    const IV: [u64; 8] = [
+        0x6a09e667f3bcc908,
+        0xbb67ae8584caa73b,
+        0x3c6ef372fe94f82b,
+        0xa54ff53a5f1d36f1,
+        0x510e527fade682d1,
+        0x9b05688c2b3e6c1f,
+        0x1f83d9abfb41bd6b,
+        0x5be0cd19137e2179,
+    ];
+    fn from_le_slice(bytes: &[u8]) -> Self {
+        u64::from_le_bytes(bytes.try_into().unwrap())
+    }
+    fn to_le_vec(self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+    fn from_u64(x: u64) -> Self {
+        x
+    }
+    fn wrapping_add(self, other: Self) -> Self {
+        u64::wrapping_add(self, other)
+    }
+    fn rotate_right(self, n: u32) -> Self {
+        u64::rotate_right(self, n)
+    }
+    fn not(self) -> Self {
+        !self
+    }
+}
+
+impl Word for u32 {
+    const BYTES: usize = 4;
+    const ROUNDS: usize = 10;
+    const ROTATIONS: [u32; 4] = [16, 12, 8, 7];
+    const IV: [u32; 8] = [
+        0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB,
+        0x5BE0CD19,
+    ];
+    fn from_le_slice(bytes: &[u8]) -> Self {
+        u32::from_le_bytes(bytes.try_into().unwrap())
+    }
+    fn to_le_vec(self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+    fn from_u64(x: u64) -> Self {
+        x as u32
+    }
+    fn wrapping_add(self, other: Self) -> Self {
+        u32::wrapping_add(self, other)
+    }
+    fn rotate_right(self, n: u32) -> Self {
+        u32::rotate_right(self, n)
+    }
+    fn not(self) -> Self {
+        !self
+    }
+}
+
+/// The G mixing function, mixes two input words x and y into four words of the working vector.
+fn mix<W: Word>(v: &mut [W; 16], a: usize, b: usize, c: usize, d: usize, x: W, y: W) {
+    let [r1, r2, r3, r4] = W::ROTATIONS;
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(r1);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(r2);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(r3);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(r4);
+}
+
+/// The compression function F. `counter` is the number of bytes processed so far, including
+/// this block.
+fn process_block<W: Word>(h: &mut [W; 8], block: &[u8], counter: u128, is_last: bool) {
+    let mut m = [W::default(); 16];
+    for (word, chunk) in m.iter_mut().zip(block.chunks_exact(W::BYTES)) {
+        *word = W::from_le_slice(chunk);
+    }
+
+    let mut v = [W::default(); 16];
+    v[..8].copy_from_slice(h);
+    v[8..].copy_from_slice(&W::IV);
+    // The counter is 2 words wide.
+    let bits = W::BYTES as u32 * 8;
+    v[12] = v[12] ^ W::from_u64(counter as u64);
+    v[13] = v[13] ^ W::from_u64((counter >> bits) as u64);
+    if is_last {
+        v[14] = v[14].not();
+    }
+
+    for round in 0..W::ROUNDS {
+        let s = &SIGMA[round % 10];
+        mix(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        mix(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        mix(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        mix(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        mix(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        mix(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        mix(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        mix(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        h[i] = h[i] ^ v[i] ^ v[i + 8];
+    }
+}
+
+fn blake2<W: Word>(
+    payload: &[u8],
+    digest_len: usize,
+    key: &[u8],
+    salt: &[u8],
+    personal: &[u8],
+) -> Vec<u8> {
+    // Block size is 16 words, digest and key can be up to 8 words, salt and personal 2 words.
+    let block_size = 16 * W::BYTES;
+    assert!(
+        (1..=8 * W::BYTES).contains(&digest_len),
+        "invalid digest length"
+    );
+    assert!(key.len() <= 8 * W::BYTES, "key is too long");
+    assert!(salt.len() <= 2 * W::BYTES, "salt is too long");
+    assert!(
+        personal.len() <= 2 * W::BYTES,
+        "personalization is too long"
+    );
+
+    // The parameter block is xored with the IV. Its first word holds the digest length, key
+    // length, fanout and depth (both 1 for sequential hashing).
+    let mut parameter_block = vec![0u8; 8 * W::BYTES];
+    parameter_block[0] = digest_len as u8;
+    parameter_block[1] = key.len() as u8;
+    parameter_block[2] = 1;
+    parameter_block[3] = 1;
+    parameter_block[4 * W::BYTES..4 * W::BYTES + salt.len()].copy_from_slice(salt);
+    parameter_block[6 * W::BYTES..6 * W::BYTES + personal.len()].copy_from_slice(personal);
+
+    let mut h = W::IV;
+    for (word, chunk) in h.iter_mut().zip(parameter_block.chunks_exact(W::BYTES)) {
+        *word = *word ^ W::from_le_slice(chunk);
+    }
+
+    // If there is a key, it's padded with zeros to a full block and prepended to the message.
+    let mut data = vec![];
+    if !key.is_empty() {
+        data.extend_from_slice(key);
+        data.resize(block_size, 0);
+    }
+    data.extend_from_slice(payload);
+
+    if data.is_empty() {
+        process_block(&mut h, &vec![0; block_size], 0, true);
+    }
+    let blocks = data.len().div_ceil(block_size);
+    let mut counter = 0u128;
+    for (i, block) in data.chunks(block_size).enumerate() {
+        counter += block.len() as u128;
+        // The last block is padded with zeros, the counter doesn't include the padding.
+        let mut buf = block.to_vec();
+        buf.resize(block_size, 0);
+        process_block(&mut h, &buf, counter, i == blocks - 1);
+    }
+
+    let mut ret: Vec<u8> = h.iter().flat_map(|word| word.to_le_vec()).collect();
+    ret.truncate(digest_len);
+    ret
+}
+
+/// Produce a 64 bytes BLAKE2b hash of payload.
+pub fn blake2b(payload: &[u8]) -> Vec<u8> {
+    blake2b_params(payload, 64, &[], &[], &[])
+}
+
+/// BLAKE2b with all the parameters. `digest_len` is in 1..=64, `key` up to 64 bytes, `salt`
+/// and `personal` up to 16 bytes each. Empty slices mean the parameter isn't used.
+pub fn blake2b_params(
+    payload: &[u8],
+    digest_len: usize,
+    key: &[u8],
+    salt: &[u8],
+    personal: &[u8],
+) -> Vec<u8> {
+    blake2::<u64>(payload, digest_len, key, salt, personal)
+}
+
+/// Produce a 32 bytes BLAKE2s hash of payload.
+pub fn blake2s(payload: &[u8]) -> Vec<u8> {
+    blake2s_params(payload, 32, &[], &[], &[])
+}
+
+/// BLAKE2s with all the parameters. `digest_len` is in 1..=32, `key` up to 32 bytes, `salt`
+/// and `personal` up to 8 bytes each. Empty slices mean the parameter isn't used.
+pub fn blake2s_params(
+    payload: &[u8],
+    digest_len: usize,
+    key: &[u8],
+    salt: &[u8],
+    personal: &[u8],
+) -> Vec<u8> {
+    blake2::<u32>(payload, digest_len, key, salt, personal)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::blake2::{blake2b, blake2b_params, blake2s, blake2s_params};
+    use crate::hash::to_hex;
+    use std::assert_eq;
+
+    #[test]
+    fn test_blake2b() {
+        let tests: [(&[u8], &str); 3] = [
+            (
+                b"abc",
+                "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+            ),
+            (
+                b"",
+                "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce",
+            ),
+            (
+                &[0; 128],
+                "865939e120e6805438478841afb739ae4250cf372653078a065cdcfffca4caf798e6d462b65d658fc165782640eded70963449ae1500fb0f24981d7727e22c41",
+            ),
+        ];
+        for (input, expected) in tests {
+            assert_eq!(to_hex(blake2b(input)), expected);
+        }
+    }
+
+    #[test]
+    fn test_blake2s() {
+        let tests: [(&[u8], &str); 3] = [
+            (
+                b"abc",
+                "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+            ),
+            (
+                b"",
+                "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9",
+            ),
+            (
+                &[0; 64],
+                "ae09db7cd54f42b490ef09b6bc541af688e4959bb8c53f359a6f56e38ab454a3",
+            ),
+        ];
+        for (input, expected) in tests {
+            assert_eq!(to_hex(blake2s(input)), expected);
+        }
+    }
+
+    #[test]
+    fn test_params() {
+        // Reference: python's hashlib.blake2b / blake2s.
+        let message = b"The quick brown fox jumps over the lazy dog";
+        let received = blake2b_params(
+            message,
+            32,
+            b"secret",
+            b"0123456789abcdef",
+            b"cryptopals rules",
+        );
+        assert_eq!(
+            to_hex(received),
+            "a7391e03b26376856bcd3881c8adecccd811d9e1e2af3680d5dc2766efacc4d4"
+        );
+        let received = blake2s_params(message, 16, b"secret", b"saltsalt", b"personal");
+        assert_eq!(to_hex(received), "ce8aafba131b93b2b35ef935faf37013");
+    }
+}
@@ -0,0 +1,307 @@
+//! Generic collision search for truncated hashes.
+//!
+//! All the searches are generic over a function from bytes to an N bytes digest, e.g. the first
+//! N bytes of `sha1` or of a MAC under a fixed key. By the birthday paradox a collision is
+//! expected after about sqrt(pi/2 * 2^(8N)) evaluations. The methods differ in memory:
+//! * `birthday` stores every digest: fastest, but needs memory for all of them.
+//! * `rho_floyd` and `rho_brent` walk x -> f(x) until the walk cycles (Pollard's rho), using
+//!   constant memory, at the price of a few times more evaluations.
+//! * `parallel_rho` is the van Oorschot–Wiener distinguished points method: many walks in
+//!   parallel, only storing the points with some leading zero bits.
+//!
+//! https://people.scs.carleton.ca/~paulv/papers/JoC97.pdf
+
+use rand::Rng;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// A pair of different inputs with the same digest, and the work done to find it.
+#[derive(Debug, Clone)]
+pub struct Collision<const N: usize> {
+    pub first: Vec<u8>,
+    pub second: Vec<u8>,
+    pub digest: [u8; N],
+    /// Number of evaluations of the function.
+    pub evaluations: u64,
+    /// The birthday bound for an N bytes digest, to compare with `evaluations`.
+    pub expected_evaluations: f64,
+}
+
+/// Expected number of evaluations to find a collision on a `digest_size` bytes digest.
+pub fn expected_evaluations(digest_size: usize) -> f64 {
+    (PI / 2.0 * 2f64.powi(8 * digest_size as i32)).sqrt()
+}
+
+/// Wraps `f` to count how many times it's called.
+struct Counted<F> {
+    f: F,
+    evaluations: AtomicU64,
+}
+impl<F> Counted<F> {
+    fn new(f: F) -> Self {
+        Self {
+            f,
+            evaluations: AtomicU64::new(0),
+        }
+    }
+    fn call<const N: usize>(&self, input: &[u8]) -> [u8; N]
+    where
+        F: Fn(&[u8]) -> [u8; N],
+    {
+        self.evaluations.fetch_add(1, Ordering::Relaxed);
+        (self.f)(input)
+    }
+    fn collision<const N: usize>(
+        &self,
+        first: Vec<u8>,
+        second: Vec<u8>,
+        digest: [u8; N],
+    ) -> Collision<N> {
+        Collision {
+            first,
+            second,
+            digest,
+            evaluations: self.evaluations.load(Ordering::Relaxed),
+            expected_evaluations: expected_evaluations(N),
+        }
+    }
+}
+
+fn random_point<const N: usize>(rng: &mut impl Rng) -> [u8; N] {
+    let mut ret = [0u8; N];
+    rng.fill(&mut ret[..]);
+    ret
+}
+
+/// The naive birthday attack: hash random N bytes inputs and store all the digests.
+pub fn birthday<const N: usize>(f: impl Fn(&[u8]) -> [u8; N]) -> Collision<N> {
+    let f = Counted::new(f);
+    let mut rng = rand::thread_rng();
+    let mut seen: HashMap<[u8; N], [u8; N]> = HashMap::new();
+    loop {
+        let input: [u8; N] = random_point(&mut rng);
+        let digest = f.call(&input);
+        match seen.get(&digest) {
+            Some(other) if *other != input => {
+                return f.collision(other.to_vec(), input.to_vec(), digest)
+            }
+            _ => {
+                seen.insert(digest, input);
+            }
+        }
+    }
+}
+
+/// Given two points `a` and `b`, and knowing that walking from them eventually merges, finds the
+/// two different points whose digests are the merge point. `a` must be at least as far from the
+/// merge point as `b`, `distance` steps further.
+/// Returns `None` if the walks merge straight away (`a` is the same as `b` after `distance`
+/// steps): that's not a collision.
+fn locate_collision<const N: usize, F: Fn(&[u8]) -> [u8; N]>(
+    f: &Counted<F>,
+    mut a: [u8; N],
+    mut b: [u8; N],
+    distance: u64,
+) -> Option<Collision<N>> {
+    for _ in 0..distance {
+        a = f.call(&a);
+    }
+    if a == b {
+        return None;
+    }
+    loop {
+        let (next_a, next_b) = (f.call(&a), f.call(&b));
+        if next_a == next_b {
+            return Some(f.collision(a.to_vec(), b.to_vec(), next_a));
+        }
+        a = next_a;
+        b = next_b;
+    }
+}
+
+/// Pollard's rho with Floyd's cycle finding: the tortoise moves one step, the hare two, until
+/// they meet on the cycle. Then the tortoise restarts and both move one step at a time: they
+/// meet at the entrance of the cycle, reached from two different points.
+pub fn rho_floyd<const N: usize>(f: impl Fn(&[u8]) -> [u8; N]) -> Collision<N> {
+    let f = Counted::new(f);
+    let mut rng = rand::thread_rng();
+    loop {
+        let start: [u8; N] = random_point(&mut rng);
+        let mut tortoise = f.call(&start);
+        let mut hare = f.call(&tortoise);
+        while tortoise != hare {
+            tortoise = f.call(&tortoise);
+            hare = f.call(&f.call(&hare));
+        }
+        // If the start is already on the cycle there's no collision, try another start.
+        if let Some(collision) = locate_collision(&f, start, hare, 0) {
+            return collision;
+        }
+    }
+}
+
+/// Pollard's rho with Brent's cycle finding: it finds the cycle length λ by teleporting the
+/// tortoise to the hare at every power of two, which takes fewer evaluations than Floyd.
+/// Then two walks λ steps apart meet at the entrance of the cycle.
+pub fn rho_brent<const N: usize>(f: impl Fn(&[u8]) -> [u8; N]) -> Collision<N> {
+    let f = Counted::new(f);
+    let mut rng = rand::thread_rng();
+    loop {
+        let start: [u8; N] = random_point(&mut rng);
+        let mut power = 1;
+        let mut lambda = 1;
+        let mut tortoise = start;
+        let mut hare = f.call(&start);
+        while tortoise != hare {
+            if power == lambda {
+                tortoise = hare;
+                power *= 2;
+                lambda = 0;
+            }
+            hare = f.call(&hare);
+            lambda += 1;
+        }
+        if let Some(collision) = locate_collision(&f, start, start, lambda) {
+            return collision;
+        }
+    }
+}
+
+/// The van Oorschot–Wiener parallel collision search. Each thread walks from a random point
+/// until it reaches a distinguished point, a digest whose first `distinguished_bits` bits are
+/// zero, and stores it with the start of the walk. When two walks end in the same distinguished
+/// point they merged somewhere: walking them again from the start finds the collision.
+/// `threads` is at least 1, 0 runs a single thread.
+pub fn parallel_rho<const N: usize>(
+    f: impl Fn(&[u8]) -> [u8; N] + Sync,
+    threads: usize,
+    distinguished_bits: u32,
+) -> Collision<N> {
+    assert!(distinguished_bits < 8 * N as u32);
+    // 20 << distinguished_bits must fit in the u64 walk length.
+    assert!(
+        distinguished_bits < u64::BITS - 5,
+        "At most {} distinguished bits",
+        u64::BITS - 6
+    );
+    let f = Counted::new(f);
+    // Distinguished point -> (start of the walk, length of the walk)
+    let trails = Mutex::new(HashMap::<[u8; N], ([u8; N], u64)>::new());
+    let found = AtomicBool::new(false);
+    let result = Mutex::new(None);
+    // A walk stuck in a cycle without distinguished points is abandoned after this many steps.
+    let max_length = 20u64 << distinguished_bits;
+
+    let is_distinguished = |x: &[u8; N]| {
+        let leading_zeros: u32 = x
+            .iter()
+            .position(|b| *b != 0)
+            .map(|i| 8 * i as u32 + x[i].leading_zeros())
+            .unwrap_or(8 * N as u32);
+        leading_zeros >= distinguished_bits
+    };
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| {
+                let mut rng = rand::thread_rng();
+                while !found.load(Ordering::Relaxed) {
+                    let start: [u8; N] = random_point(&mut rng);
+                    let mut x = start;
+                    let mut length = 0u64;
+                    while !is_distinguished(&x) && length < max_length {
+                        x = f.call(&x);
+                        length += 1;
+                    }
+                    if length == max_length {
+                        continue;
+                    }
+                    let previous = trails.lock().unwrap().insert(x, (start, length));
+                    if let Some((other_start, other_length)) = previous {
+                        let collision = if length >= other_length {
+                            locate_collision(&f, start, other_start, length - other_length)
+                        } else {
+                            locate_collision(&f, other_start, start, other_length - length)
+                        };
+                        if let Some(collision) = collision {
+                            found.store(true, Ordering::Relaxed);
+                            result.lock().unwrap().get_or_insert(collision);
+                        }
+                    }
+                }
+            });
+        }
+    });
+    let mut collision = result.into_inner().unwrap().unwrap();
+    collision.evaluations = f.evaluations.load(Ordering::Relaxed);
+    collision
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::collision::{birthday, parallel_rho, rho_brent, rho_floyd, Collision};
+    use crate::hash::md4::md4;
+    use crate::hash::sha1::sha1;
+    use crate::mac::hmac_sha1;
+
+    fn truncated_sha1(input: &[u8]) -> [u8; 3] {
+        let mut ret = [0u8; 3];
+        ret.copy_from_slice(&sha1(input)[..3]);
+        ret
+    }
+
+    fn check<const N: usize>(collision: Collision<N>, f: impl Fn(&[u8]) -> [u8; N]) {
+        assert_ne!(collision.first, collision.second);
+        assert_eq!(f(&collision.first), collision.digest);
+        assert_eq!(f(&collision.second), collision.digest);
+        println!(
+            "Evaluations: {}, expected: {:.0}",
+            collision.evaluations, collision.expected_evaluations
+        );
+        // Way more than expected means something is wrong.
+        assert!((collision.evaluations as f64) < 50.0 * collision.expected_evaluations);
+    }
+
+    #[test]
+    fn test_birthday() {
+        check(birthday(truncated_sha1), truncated_sha1);
+        let truncated_md4 = |input: &[u8]| {
+            let mut ret = [0u8; 3];
+            ret.copy_from_slice(&md4(input)[..3]);
+            ret
+        };
+        check(birthday(truncated_md4), truncated_md4);
+    }
+
+    #[test]
+    fn test_rho() {
+        check(rho_floyd(truncated_sha1), truncated_sha1);
+        check(rho_brent(truncated_sha1), truncated_sha1);
+        let truncated_mac = |input: &[u8]| {
+            let mut ret = [0u8; 3];
+            ret.copy_from_slice(&hmac_sha1(b"YELLOW SUBMARINE", input)[..3]);
+            ret
+        };
+        check(rho_brent(truncated_mac), truncated_mac);
+    }
+
+    #[test]
+    fn test_parallel_rho() {
+        check(parallel_rho(truncated_sha1, 4, 6), truncated_sha1);
+        check(parallel_rho(truncated_sha1, 0, 6), truncated_sha1);
+    }
+
+    #[test]
+    #[should_panic(expected = "At most 58 distinguished bits")]
+    fn test_parallel_rho_too_many_distinguished_bits() {
+        let sha1_digest = |input: &[u8]| {
+            let mut ret = [0u8; 20];
+            ret.copy_from_slice(&sha1(input));
+            ret
+        };
+        parallel_rho(sha1_digest, 1, 60);
+    }
+}
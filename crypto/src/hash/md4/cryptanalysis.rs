@@ -0,0 +1,329 @@
+//! MD4 collisions, following "Cryptanalysis of the Hash Functions MD4 and RIPEMD" by Wang et al.
+//! https://cryptopals.com/sets/7/challenges/55
+//!
+//! The attack uses a fixed message difference:
+//! M' = M + (2^31 on m1) + (2^31 - 2^28 on m2) - (2^16 on m12)
+//! and a differential path through the compression function that holds when a list of
+//! sufficient conditions on the intermediate state is satisfied.
+//! All the first round conditions are satisfied directly ("single-step message modification"),
+//! because in the first round each state word depends on a single message word. Part of the
+//! second round conditions are corrected too ("multi-step message modification"), by tweaking a
+//! message word used in the second round and fixing the first round words it disturbs.
+//! What's left holds with a small enough probability to brute force it.
+
+use crate::hash::md4::{f, g, process_block, Md4State, S};
+use rand::Rng;
+
+const ROUND_2_CONSTANT: u32 = 0x5a827999;
+
+/// A condition on a single bit of an intermediate state word.
+#[derive(Debug, Copy, Clone)]
+enum Condition {
+    Zero,
+    One,
+    /// Equal to the same bit of the state word computed `n` steps before.
+    Eq(usize),
+    /// Different from the same bit of the state word computed `n` steps before.
+    Neq(usize),
+}
+use Condition::*;
+
+/// The intermediate state words are numbered in the order they are computed: 0..4 are the
+/// initial a0, d0, c0, b0, then 4 is a1, 5 is d1, 6 is c1, 7 is b1, 8 is a2 and so on.
+/// Bits are numbered from 1 to 32 as in the paper's table.
+const CONDITIONS: &[(usize, u32, Condition)] = &[
+    // a1
+    (4, 7, Eq(1)),
+    // d1
+    (5, 7, Zero),
+    (5, 8, Eq(1)),
+    (5, 11, Eq(1)),
+    // c1
+    (6, 7, One),
+    (6, 8, One),
+    (6, 11, Zero),
+    (6, 26, Eq(1)),
+    // b1
+    (7, 7, One),
+    (7, 8, Zero),
+    (7, 11, Zero),
+    (7, 26, Zero),
+    // a2
+    (8, 8, One),
+    (8, 11, One),
+    (8, 26, Zero),
+    (8, 14, Eq(1)),
+    // d2
+    (9, 14, Zero),
+    (9, 19, Eq(1)),
+    (9, 20, Eq(1)),
+    (9, 21, Eq(1)),
+    (9, 22, Eq(1)),
+    (9, 26, One),
+    // c2
+    (10, 13, Eq(1)),
+    (10, 14, Zero),
+    (10, 15, Eq(1)),
+    (10, 19, Zero),
+    (10, 20, Zero),
+    (10, 21, One),
+    (10, 22, Zero),
+    // b2
+    (11, 13, One),
+    (11, 14, One),
+    (11, 15, Zero),
+    (11, 17, Eq(1)),
+    (11, 19, Zero),
+    (11, 20, Zero),
+    (11, 21, Zero),
+    (11, 22, Zero),
+    // a3
+    (12, 13, One),
+    (12, 14, One),
+    (12, 15, One),
+    (12, 17, Zero),
+    (12, 19, Zero),
+    (12, 20, Zero),
+    (12, 21, Zero),
+    (12, 23, Eq(1)),
+    (12, 22, One),
+    (12, 26, Eq(1)),
+    // d3
+    (13, 13, One),
+    (13, 14, One),
+    (13, 15, One),
+    (13, 17, Zero),
+    (13, 20, Zero),
+    (13, 21, One),
+    (13, 22, One),
+    (13, 23, Zero),
+    (13, 26, One),
+    (13, 30, Eq(1)),
+    // c3
+    (14, 17, One),
+    (14, 20, Zero),
+    (14, 21, Zero),
+    (14, 22, Zero),
+    (14, 23, Zero),
+    (14, 26, Zero),
+    (14, 30, One),
+    (14, 32, Eq(1)),
+    // b3
+    (15, 20, Zero),
+    (15, 21, One),
+    (15, 22, One),
+    (15, 23, Eq(1)),
+    (15, 26, One),
+    (15, 30, Zero),
+    (15, 32, Zero),
+    // a4
+    (16, 23, Zero),
+    (16, 26, Zero),
+    (16, 27, Eq(1)),
+    (16, 29, Eq(1)),
+    (16, 30, One),
+    (16, 32, Zero),
+    // d4
+    (17, 23, Zero),
+    (17, 26, Zero),
+    (17, 27, One),
+    (17, 29, One),
+    (17, 30, Zero),
+    (17, 32, One),
+    // c4
+    (18, 19, Eq(1)),
+    (18, 23, One),
+    (18, 26, One),
+    (18, 27, Zero),
+    (18, 29, Zero),
+    (18, 30, Zero),
+    // b4
+    (19, 19, Zero),
+    (19, 26, Eq(1)),
+    (19, 27, One),
+    (19, 29, One),
+    (19, 30, Zero),
+    // a5
+    (20, 19, Eq(2)),
+    (20, 26, One),
+    (20, 27, Zero),
+    (20, 29, One),
+    (20, 32, One),
+    // d5
+    (21, 19, Eq(1)),
+    (21, 26, Eq(2)),
+    (21, 27, Eq(2)),
+    (21, 29, Eq(2)),
+    (21, 32, Eq(2)),
+    // c5
+    (22, 26, Eq(1)),
+    (22, 27, Eq(1)),
+    (22, 29, Eq(1)),
+    (22, 30, Eq(1)),
+    (22, 32, Eq(1)),
+    // b5
+    (23, 29, Eq(1)),
+    (23, 30, One),
+    (23, 32, Zero),
+    // a6
+    (24, 29, One),
+    (24, 32, One),
+    // d6
+    (25, 29, Eq(2)),
+    // c6
+    (26, 29, Eq(1)),
+    (26, 30, Neq(1)),
+    (26, 32, Neq(1)),
+];
+
+/// Returns `word` with the conditions on state word `index` satisfied.
+fn satisfy(q: &[u32], index: usize, mut word: u32) -> u32 {
+    for &(_, bit, condition) in CONDITIONS.iter().filter(|(i, _, _)| *i == index) {
+        let mask = 1 << (bit - 1);
+        word = match condition {
+            Zero => word & !mask,
+            One => word | mask,
+            Eq(n) => (word & !mask) | (q[index - n] & mask),
+            Neq(n) => (word & !mask) | (!q[index - n] & mask),
+        };
+    }
+    word
+}
+
+fn is_satisfied(q: &[u32], index: usize) -> bool {
+    satisfy(q, index, q[index]) == q[index]
+}
+
+/// Step `i` of the first round: computes state word i + 4 from message word i.
+fn round_1(q: &[u32], m: u32, i: usize) -> u32 {
+    q[i].wrapping_add(f(q[i + 3], q[i + 2], q[i + 1]))
+        .wrapping_add(m)
+        .rotate_left(S[0][i % 4])
+}
+
+/// The inverse of `round_1`: the message word that makes step `i` produce `q[i + 4]`.
+fn round_1_message(q: &[u32], i: usize) -> u32 {
+    q[i + 4]
+        .rotate_right(S[0][i % 4])
+        .wrapping_sub(q[i])
+        .wrapping_sub(f(q[i + 3], q[i + 2], q[i + 1]))
+}
+
+/// Step `i` of the second round, `m` is the message word for this step.
+fn round_2(q: &[u32], m: u32, i: usize) -> u32 {
+    q[i].wrapping_add(g(q[i + 3], q[i + 2], q[i + 1]))
+        .wrapping_add(m)
+        .wrapping_add(ROUND_2_CONSTANT)
+        .rotate_left(S[1][i % 4])
+}
+
+/// The message word that makes step `i` of the second round produce `target`.
+fn round_2_message(q: &[u32], i: usize, target: u32) -> u32 {
+    target
+        .rotate_right(S[1][i % 4])
+        .wrapping_sub(q[i])
+        .wrapping_sub(g(q[i + 3], q[i + 2], q[i + 1]))
+        .wrapping_sub(ROUND_2_CONSTANT)
+}
+
+/// Single-step modification: computes each first round state word, fixes its bits, and derives
+/// the message word that produces it. Returns the modified message and the state words.
+fn first_round(mut m: [u32; 16]) -> ([u32; 16], Vec<u32>) {
+    let initial = Md4State::new();
+    let mut q = vec![initial.a, initial.d, initial.c, initial.b];
+    for (i, m_i) in m.iter_mut().enumerate() {
+        let word = round_1(&q, *m_i, i);
+        q.push(satisfy(&q, i + 4, word));
+        *m_i = round_1_message(&q, i);
+    }
+    (m, q)
+}
+
+/// Modifies the message so that the first round conditions hold, and corrects the a5 and d5
+/// conditions. Returns the modified message and whether all the checked conditions hold.
+fn modify_message(m: [u32; 16]) -> ([u32; 16], bool) {
+    let (mut m, mut q) = first_round(m);
+
+    // Multi-step modification. The second round steps 0 and 1 (a5 and d5) use m0 and m4.
+    // Changing m0 (m4) to fix a5 (d5) changes a1 (a2): the following four message words are
+    // recomputed so that the rest of the first round is left untouched.
+    for (step, message_index) in [(0, 0), (1, 4)] {
+        let word = round_2(&q, m[message_index], step + 16);
+        let target = satisfy(&q, step + 20, word);
+        if word != target {
+            m[message_index] = round_2_message(&q, step + 16, target);
+            q[message_index + 4] = round_1(&q, m[message_index], message_index);
+            for (i, m_i) in m.iter_mut().enumerate().skip(message_index + 1).take(4) {
+                *m_i = round_1_message(&q, i);
+            }
+        }
+        q.push(target);
+    }
+
+    // The remaining second round steps are only checked.
+    for step in 2..8 {
+        let message_index = [0, 4, 8, 12, 1, 5, 9, 13][step];
+        q.push(round_2(&q, m[message_index], step + 16));
+    }
+    // Carries during the correction can break some conditions.
+    let valid = (4..q.len()).all(|index| is_satisfied(&q, index));
+    (m, valid)
+}
+
+/// Applies the message difference of the differential path.
+fn apply_difference(m: &[u32; 16]) -> [u32; 16] {
+    let mut m_prime = *m;
+    m_prime[1] = m[1].wrapping_add(1 << 31);
+    m_prime[2] = m[2].wrapping_add(1 << 31).wrapping_sub(1 << 28);
+    m_prime[12] = m[12].wrapping_sub(1 << 16);
+    m_prime
+}
+
+fn to_bytes(m: &[u32; 16]) -> Vec<u8> {
+    m.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+/// Finds two different 64 bytes messages with the same md4 hash.
+/// Returns the two messages and the number of candidates tried.
+pub fn find_collision() -> (Vec<u8>, Vec<u8>, u64) {
+    let mut rng = rand::thread_rng();
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let (m, valid) = modify_message(rng.gen());
+        if !valid {
+            continue;
+        }
+        let m_prime = apply_difference(&m);
+        let (block, block_prime) = (to_bytes(&m), to_bytes(&m_prime));
+        if process_block(Md4State::new(), &block) == process_block(Md4State::new(), &block_prime) {
+            return (block, block_prime, attempts);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::md4::cryptanalysis::find_collision;
+    use crate::hash::md4::cryptanalysis::{first_round, is_satisfied, round_1};
+    use crate::hash::md4::md4;
+    use rand::Rng;
+
+    #[test]
+    fn test_first_round() {
+        // The first round conditions always hold after the single-step modification.
+        let (m, q) = first_round(rand::thread_rng().gen());
+        for i in 0..16 {
+            assert_eq!(round_1(&q, m[i], i), q[i + 4]);
+            assert!(is_satisfied(&q, i + 4), "Failed on state word {}", i + 4);
+        }
+    }
+
+    #[test]
+    fn test_find_collision() {
+        let (first, second, attempts) = find_collision();
+        println!("Collision found after {} attempts", attempts);
+        assert_ne!(first, second);
+        assert_eq!(md4(&first), md4(&second));
+    }
+}
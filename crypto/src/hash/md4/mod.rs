@@ -1,6 +1,8 @@
 use std::convert::TryInto;
 use std::mem;
 
+pub mod cryptanalysis;
+
 /// The MD4 Message-Digest Algorithm implementation
 /// https://www.rfc-editor.org/rfc/rfc1320
 ///
@@ -38,7 +40,7 @@ pub fn md4_state_len(
     (result.to_vec(), state)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Md4State {
     a: u32,
     b: u32,
@@ -83,7 +85,8 @@ fn h(x: u32, y: u32, z: u32) -> u32 {
 }
 pub fn md4_padding_needed(message_size: usize) -> usize {
     const SIZE_OF_ONE: usize = mem::size_of::<u8>();
-    BLOCK_SIZE - (message_size + SIZE_OF_ONE + mem::size_of::<u64>()) % BLOCK_SIZE
+    // The modulo keeps a message ending exactly on the block boundary from getting a full block.
+    (BLOCK_SIZE - (message_size + SIZE_OF_ONE + mem::size_of::<u64>()) % BLOCK_SIZE) % BLOCK_SIZE
 }
 /// The message M is padded so that its length (in bits) is equal to 448 modulo 512, that is,
 /// the padded message is 64 bits less than a multiple of 512.
@@ -176,6 +179,10 @@ mod tests {
                 "The quick brown fox jumps over the lazy dog",
                 "1bee69a46ba811185c194762abaeae90",
             ),
+            (
+                "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "c889c81dd86c4d2e025778944ea02881",
+            ),
         ];
         for (input, expected) in tests {
             assert_eq!(
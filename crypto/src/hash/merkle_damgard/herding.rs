@@ -0,0 +1,159 @@
+//! The herding attack, a.k.a. the Nostradamus attack, by Kelsey and Kohno.
+//! https://cryptopals.com/sets/7/challenges/54
+//!
+//! We publish a hash claiming it's the hash of a prediction, e.g. the final scores of all the
+//! baseball games of the season. When the season is over, we "reveal" a message starting with
+//! the right scores and hashing to the published value.
+//!
+//! The trick is the diamond structure: start from 2^k random states, pair them and find a
+//! collision for each pair (a block for each state, leading both to the same state). That halves
+//! the states, repeat until a single one is left: its hash (after the padding for the final
+//! length) is the prediction. Later, given the prefix, finding a linking block from the prefix
+//! state to any of the 2^k leaves costs 2^(b-k), then the path from the leaf to the root is known.
+
+use crate::hash::merkle_damgard::multicollision::{find_collision_from, random_block};
+use crate::hash::merkle_damgard::MerkleDamgard;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+/// A node of the diamond structure: its state, and the block leading to its parent node.
+#[derive(Debug, Clone)]
+struct Node {
+    state: Vec<u8>,
+    block: Vec<u8>,
+}
+
+/// A binary tree of collisions: the states of level i + 1 are reached from pairs of states of
+/// level i. The node j of level i has parent j / 2.
+#[derive(Debug, Clone)]
+pub struct DiamondStructure {
+    levels: Vec<Vec<Node>>,
+    root: Vec<u8>,
+}
+
+impl DiamondStructure {
+    /// Builds a diamond structure with 2^k leaves, it costs about 2^k * 2^(b/2) calls.
+    /// k must be at least 1: a single leaf has nothing to collide with.
+    pub fn generate(hash: &MerkleDamgard, k: usize) -> Self {
+        assert!(k > 0, "A diamond structure needs at least 2 leaves");
+        let mut rng = rand::thread_rng();
+        let mut leaves = HashSet::new();
+        while leaves.len() < 1 << k {
+            let state: Vec<u8> = (0..hash.state_size()).map(|_| rng.gen()).collect();
+            leaves.insert(state);
+        }
+        let mut states: Vec<Vec<u8>> = leaves.into_iter().collect();
+
+        let mut levels = vec![];
+        while states.len() > 1 {
+            let mut level = vec![];
+            let mut next_states = vec![];
+            for pair in states.chunks(2) {
+                let (first, second, state) = find_collision_from(hash, &pair[0], &pair[1]);
+                level.push(Node {
+                    state: pair[0].clone(),
+                    block: first,
+                });
+                level.push(Node {
+                    state: pair[1].clone(),
+                    block: second,
+                });
+                next_states.push(state);
+            }
+            levels.push(level);
+            states = next_states;
+        }
+        let root = states.pop().unwrap();
+        Self { levels, root }
+    }
+
+    /// Number of blocks from a leaf to the root.
+    pub fn depth(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn leaves(&self) -> impl Iterator<Item = &[u8]> {
+        self.levels[0].iter().map(|node| node.state.as_slice())
+    }
+
+    /// The hash to publish, for messages with a prefix of `prefix_blocks` blocks.
+    /// The messages will have a linking block and `depth` blocks after the prefix.
+    pub fn prediction(&self, hash: &MerkleDamgard, prefix_blocks: usize) -> Vec<u8> {
+        let message_size = (prefix_blocks + 1 + self.depth()) * hash.block_size();
+        hash.hash_state_len(&self.root, &[], message_size).0
+    }
+
+    /// The blocks leading from the `leaf`-th leaf to the root.
+    fn path(&self, mut leaf: usize) -> Vec<u8> {
+        let mut ret = vec![];
+        for level in &self.levels {
+            ret.extend_from_slice(&level[leaf].block);
+            leaf /= 2;
+        }
+        ret
+    }
+}
+
+/// Returns a message starting with `prefix` and hashing to `diamond.prediction`.
+/// The prefix is padded with zeros to a full block, and it must have the number of blocks the
+/// prediction was made for.
+pub fn herd(hash: &MerkleDamgard, diamond: &DiamondStructure, prefix: &[u8]) -> Vec<u8> {
+    let block_size = hash.block_size();
+    let mut message = prefix.to_vec();
+    message.resize(prefix.len().div_ceil(block_size) * block_size, 0);
+    let state = hash.iterate(hash.iv(), &message);
+
+    let leaves: HashMap<&[u8], usize> = diamond
+        .leaves()
+        .enumerate()
+        .map(|(i, leaf)| (leaf, i))
+        .collect();
+    loop {
+        let link = random_block(hash);
+        let link_state = hash.compress(&state, &link);
+        if let Some(&leaf) = leaves.get(link_state.as_slice()) {
+            message.extend(link);
+            message.extend(diamond.path(leaf));
+            return message;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::merkle_damgard::herding::{herd, DiamondStructure};
+    use crate::hash::merkle_damgard::{MerkleDamgard, Padding};
+
+    #[test]
+    fn test_herd() {
+        let hash = MerkleDamgard::truncated_aes(2, Padding::LengthBigEndian);
+        let k = 6;
+        let diamond = DiamondStructure::generate(&hash, k);
+        assert_eq!(diamond.depth(), k);
+        let construction_calls = hash.compression_calls();
+
+        const PREFIX_BLOCKS: usize = 2;
+        let prediction = diamond.prediction(&hash, PREFIX_BLOCKS);
+        for prefix in [
+            b"Red Sox 5 - Yankees 3, Mets 2".to_vec(),
+            b"Yankees 7 - Red Sox 0, Cubs 4".to_vec(),
+        ] {
+            let message = herd(&hash, &diamond, &prefix);
+            assert!(message.starts_with(&prefix));
+            assert_eq!(message.len(), (PREFIX_BLOCKS + 1 + k) * 16);
+            assert_eq!(hash.hash(&message), prediction);
+        }
+        println!(
+            "Diamond structure calls: {}, linking calls: {}",
+            construction_calls,
+            hash.compression_calls() - construction_calls
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 2 leaves")]
+    fn test_empty_diamond() {
+        let hash = MerkleDamgard::truncated_aes(2, Padding::LengthBigEndian);
+        DiamondStructure::generate(&hash, 0);
+    }
+}
@@ -0,0 +1,201 @@
+//! A generic Merkle–Damgård construction, to build cheap hash functions for experiments.
+//! https://cryptopals.com/sets/7/challenges/52
+//!
+//! A Merkle–Damgård hash pads the message, splits it in blocks and feeds them one by one to a
+//! compression function C(state, block) -> state, starting from an IV. The final state is the
+//! digest. sha1 and md4 are built this way, but their 160/128 bits states are too big to play
+//! with: by plugging in a compression function with a 16 or 24 bits state we can run
+//! multicollision, second preimage and herding attacks in a few seconds.
+
+use crate::aes;
+use std::iter;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub mod herding;
+pub mod multicollision;
+pub mod second_preimage;
+
+/// The compression function: takes the current state and a block, returns the new state.
+pub type CompressionFunction = Box<dyn Fn(&[u8], &[u8]) -> Vec<u8> + Send + Sync>;
+
+/// How the message is padded to a multiple of the block size.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Padding {
+    /// Zero bytes up to the block size. The empty message is hashed as the IV.
+    Zeros,
+    /// Merkle–Damgård strengthening as in sha1: a 0x80 byte, zeros and the message length in bits
+    /// as a 64-bit big-endian integer.
+    LengthBigEndian,
+    /// Merkle–Damgård strengthening as in md4, with a little-endian length.
+    LengthLittleEndian,
+}
+
+pub struct MerkleDamgard {
+    compress: CompressionFunction,
+    iv: Vec<u8>,
+    block_size: usize,
+    padding: Padding,
+    /// Number of calls to the compression function, the unit of work of the attacks.
+    calls: AtomicU64,
+}
+
+impl MerkleDamgard {
+    pub fn new(
+        compress: CompressionFunction,
+        iv: Vec<u8>,
+        block_size: usize,
+        padding: Padding,
+    ) -> Self {
+        Self {
+            compress,
+            iv,
+            block_size,
+            padding,
+            calls: AtomicU64::new(0),
+        }
+    }
+
+    /// A hash with a `state_size` bytes state, using AES as compression function:
+    /// C(H, M) = AES-128(key = H padded with zeros, M) truncated to `state_size` bytes.
+    /// The IV is made of zeros and the block size is 16 bytes.
+    pub fn truncated_aes(state_size: usize, padding: Padding) -> Self {
+        assert!(state_size <= 16, "AES can produce at most 16 bytes");
+        let compress = move |state: &[u8], block: &[u8]| {
+            let mut key = [0u8; 16];
+            key[..state.len()].copy_from_slice(state);
+            let mut buf = [0u8; 16];
+            buf.copy_from_slice(block);
+            aes::encrypt(&buf, &key)[..state_size].to_vec()
+        };
+        Self::new(Box::new(compress), vec![0; state_size], 16, padding)
+    }
+
+    pub fn iv(&self) -> &[u8] {
+        &self.iv
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    pub fn state_size(&self) -> usize {
+        self.iv.len()
+    }
+
+    /// A single call of the compression function.
+    pub fn compress(&self, state: &[u8], block: &[u8]) -> Vec<u8> {
+        assert_eq!(block.len(), self.block_size);
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        (self.compress)(state, block)
+    }
+
+    /// How many times the compression function has been called so far.
+    pub fn compression_calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// Runs the compression function over `blocks`, without any padding.
+    /// `blocks` length must be a multiple of the block size.
+    pub fn iterate(&self, state: &[u8], blocks: &[u8]) -> Vec<u8> {
+        assert_eq!(blocks.len() % self.block_size, 0);
+        blocks
+            .chunks(self.block_size)
+            .fold(state.to_vec(), |state, block| self.compress(&state, block))
+    }
+
+    /// The padding appended to a message of `message_size` bytes.
+    pub fn padding(&self, message_size: usize) -> Vec<u8> {
+        match self.padding {
+            Padding::Zeros => {
+                let padding_needed =
+                    (self.block_size - message_size % self.block_size) % self.block_size;
+                vec![0; padding_needed]
+            }
+            Padding::LengthBigEndian | Padding::LengthLittleEndian => {
+                const LENGTH_SIZE: usize = 8;
+                let padding_needed =
+                    self.block_size - (message_size + 1 + LENGTH_SIZE) % self.block_size;
+                let message_size_in_bits = (message_size as u64) * 8;
+                let mut padding = vec![0x80];
+                padding.extend(iter::repeat_n(0, padding_needed % self.block_size));
+                if self.padding == Padding::LengthBigEndian {
+                    padding.extend_from_slice(&message_size_in_bits.to_be_bytes());
+                } else {
+                    padding.extend_from_slice(&message_size_in_bits.to_le_bytes());
+                }
+                padding
+            }
+        }
+    }
+
+    /// Produce the hash of payload.
+    pub fn hash(&self, payload: &[u8]) -> Vec<u8> {
+        self.hash_state_len(&self.iv, payload, payload.len()).0
+    }
+
+    /// Produce the hash of payload, starting from `state`. Same shape as `sha1_state_len`:
+    /// `payload_length` is the length used for the padding. As the digest is the whole state,
+    /// it's also returned as the state.
+    pub fn hash_state_len(
+        &self,
+        state: &[u8],
+        payload: &[u8],
+        payload_length: usize,
+    ) -> (Vec<u8>, Vec<u8>) {
+        let mut payload = payload.to_vec();
+        payload.extend(self.padding(payload_length));
+        let state = self.iterate(state, &payload);
+        (state.clone(), state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::merkle_damgard::{MerkleDamgard, Padding};
+    use crate::hash::sha1::{sha1, Sha1State};
+    use crate::hash::to_hex;
+
+    #[test]
+    fn test_sha1_from_compression_function() {
+        let sha1_compress = |state: &[u8], block: &[u8]| {
+            let state = Sha1State::from_message_digset(state);
+            crate::hash::sha1::process_block(state, block).to_message_digest()
+        };
+        let iv = Sha1State::default().to_message_digest();
+        let hash = MerkleDamgard::new(Box::new(sha1_compress), iv, 64, Padding::LengthBigEndian);
+        for input in [
+            "",
+            "abc",
+            &"a".repeat(56),
+            &"a".repeat(64),
+            &"a".repeat(200),
+        ] {
+            assert_eq!(
+                to_hex(hash.hash(input.as_bytes())),
+                to_hex(sha1(input.as_bytes()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_truncated_aes() {
+        let hash = MerkleDamgard::truncated_aes(2, Padding::Zeros);
+        assert_eq!(hash.state_size(), 2);
+        assert_eq!(hash.hash(b"").to_vec(), vec![0, 0]);
+        assert_eq!(hash.compression_calls(), 0);
+        let digest = hash.hash(b"YELLOW SUBMARINE");
+        assert_eq!(digest.len(), 2);
+        assert_eq!(digest, hash.compress(&[0, 0], b"YELLOW SUBMARINE"));
+        assert_eq!(hash.compression_calls(), 2);
+        // Zero padding: trailing zeros don't change the hash.
+        assert_eq!(hash.hash(b"abc"), hash.hash(b"abc\0\0"));
+
+        let strengthened = MerkleDamgard::truncated_aes(3, Padding::LengthBigEndian);
+        assert_eq!(strengthened.hash(b"abc").len(), 3);
+        assert_ne!(strengthened.hash(b"abc"), strengthened.hash(b"abc\0\0"));
+        for message_size in 0..40 {
+            let padding = strengthened.padding(message_size);
+            assert_eq!((message_size + padding.len()) % 16, 0);
+        }
+    }
+}
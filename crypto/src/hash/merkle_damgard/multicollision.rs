@@ -0,0 +1,213 @@
+//! Joux multicollisions, and the attack on cascaded hashes built on them.
+//! https://cryptopals.com/sets/7/challenges/52
+//!
+//! In an iterated hash, a collision on the internal state stays a collision whatever follows.
+//! So n single block collisions found one after the other, each starting from the state the
+//! previous one ends in, give 2^n messages (pick one block of each pair) with the same hash, for
+//! the price of n birthday attacks instead of 2^n.
+//!
+//! The classic use: h(x) = f(x) || g(x) is expected to be as strong as the sum of its parts.
+//! With a b1 bits f and a b2 bits g we generate 2^(b2/2) messages colliding in f, and by the
+//! birthday paradox two of them likely collide in g too. The cost is about
+//! (b2/2) * 2^(b1/2) calls of f plus 2^(b2/2) of g: no better than attacking g alone.
+
+use crate::hash::merkle_damgard::MerkleDamgard;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Finds two different blocks that lead from `state` to the same state, with a birthday attack.
+/// Returns the two blocks and the state they both lead to.
+pub fn find_block_collision(hash: &MerkleDamgard, state: &[u8]) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let mut seen: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+    loop {
+        let block = random_block(hash);
+        let next_state = hash.compress(state, &block);
+        match seen.get(&next_state) {
+            Some(other) if *other != block => return (other.clone(), block, next_state),
+            _ => {
+                seen.insert(next_state, block);
+            }
+        }
+    }
+}
+
+/// A random block, the raw material of all the birthday attacks.
+pub fn random_block(hash: &MerkleDamgard) -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    (0..hash.block_size()).map(|_| rng.gen()).collect()
+}
+
+/// Finds a block `a` and a block `b` such that C(first, a) == C(second, b), alternating random
+/// tries from the two starting states. Returns the two blocks and the resulting state.
+pub fn find_collision_from(
+    hash: &MerkleDamgard,
+    first: &[u8],
+    second: &[u8],
+) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let mut from_first: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+    let mut from_second: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+    loop {
+        let block = random_block(hash);
+        let state = hash.compress(first, &block);
+        if let Some(other) = from_second.get(&state) {
+            return (block, other.clone(), state);
+        }
+        from_first.insert(state, block);
+
+        let block = random_block(hash);
+        let state = hash.compress(second, &block);
+        if let Some(other) = from_first.get(&state) {
+            return (other.clone(), block, state);
+        }
+        from_second.insert(state, block);
+    }
+}
+
+/// A sequence of single block collisions: 2^n messages of n blocks with the same hash.
+#[derive(Debug, Clone)]
+pub struct Multicollision {
+    /// The i-th pair holds the two choices for the i-th block.
+    pub pairs: Vec<(Vec<u8>, Vec<u8>)>,
+    /// The state all the messages lead to.
+    pub state: Vec<u8>,
+}
+
+impl Multicollision {
+    /// Generates 2^n colliding messages starting from `state`.
+    pub fn generate(hash: &MerkleDamgard, state: &[u8], n: usize) -> Self {
+        let mut ret = Self {
+            pairs: vec![],
+            state: state.to_vec(),
+        };
+        for _ in 0..n {
+            ret.extend(hash);
+        }
+        ret
+    }
+
+    /// Adds one more block collision, doubling the number of messages.
+    pub fn extend(&mut self, hash: &MerkleDamgard) {
+        let (first, second, state) = find_block_collision(hash, &self.state);
+        self.pairs.push((first, second));
+        self.state = state;
+    }
+
+    /// Number of colliding messages.
+    pub fn message_count(&self) -> usize {
+        1 << self.pairs.len()
+    }
+
+    /// The `index`-th message: the i-th bit of `index` selects the block of the i-th pair.
+    pub fn message(&self, index: usize) -> Vec<u8> {
+        self.pairs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (first, second))| {
+                if index >> i & 1 == 0 {
+                    first.clone()
+                } else {
+                    second.clone()
+                }
+            })
+            .collect()
+    }
+
+    pub fn messages(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        (0..self.message_count()).map(move |index| self.message(index))
+    }
+}
+
+/// The outcome of the attack on f || g.
+#[derive(Debug, Clone)]
+pub struct CascadeCollision {
+    pub first: Vec<u8>,
+    pub second: Vec<u8>,
+    /// Compression function calls spent on each hash.
+    pub f_calls: u64,
+    pub g_calls: u64,
+}
+
+/// Finds two messages with the same f(x) || g(x), where f is the cheaper hash.
+pub fn find_cascade_collision(f: &MerkleDamgard, g: &MerkleDamgard) -> CascadeCollision {
+    let (f_calls, g_calls) = (f.compression_calls(), g.compression_calls());
+    let g_bits = g.state_size() * 8;
+    let mut multicollision = Multicollision::generate(f, f.iv(), g_bits / 2);
+    loop {
+        if let Some((first, second)) = find_collision_in(g, &multicollision) {
+            return CascadeCollision {
+                first: multicollision.message(first),
+                second: multicollision.message(second),
+                f_calls: f.compression_calls() - f_calls,
+                g_calls: g.compression_calls() - g_calls,
+            };
+        }
+        // Unlucky, double the messages and try again.
+        multicollision.extend(f);
+    }
+}
+
+/// Hashes all the messages of the multicollision with `hash` and returns the indexes of two
+/// of them with the same hash. The states are computed level by level, so each prefix is
+/// compressed only once: 2^(n+1) calls instead of n * 2^n.
+fn find_collision_in(
+    hash: &MerkleDamgard,
+    multicollision: &Multicollision,
+) -> Option<(usize, usize)> {
+    let mut states = vec![hash.iv().to_vec()];
+    for (first, second) in &multicollision.pairs {
+        // Keep the bit order of `Multicollision::message`: the i-th pair is the i-th bit.
+        let mut next = Vec::with_capacity(states.len() * 2);
+        next.extend(states.iter().map(|state| hash.compress(state, first)));
+        next.extend(states.iter().map(|state| hash.compress(state, second)));
+        states = next;
+    }
+    let message_size = multicollision.pairs.len() * hash.block_size();
+    let mut seen = HashMap::new();
+    for (index, state) in states.into_iter().enumerate() {
+        let (digest, _) = hash.hash_state_len(&state, &[], message_size);
+        if let Some(&other) = seen.get(&digest) {
+            return Some((other, index));
+        }
+        seen.insert(digest, index);
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::merkle_damgard::multicollision::{find_cascade_collision, Multicollision};
+    use crate::hash::merkle_damgard::{MerkleDamgard, Padding};
+
+    #[test]
+    fn test_multicollision() {
+        let hash = MerkleDamgard::truncated_aes(2, Padding::LengthBigEndian);
+        let multicollision = Multicollision::generate(&hash, hash.iv(), 4);
+        assert_eq!(multicollision.message_count(), 16);
+        let messages: Vec<Vec<u8>> = multicollision.messages().collect();
+        let expected = hash.hash(&messages[0]);
+        for (i, message) in messages.iter().enumerate() {
+            assert_eq!(hash.hash(message), expected);
+            assert!(!messages[..i].contains(message));
+        }
+    }
+
+    #[test]
+    fn test_cascade_collision() {
+        let f = MerkleDamgard::truncated_aes(2, Padding::LengthBigEndian);
+        let g = MerkleDamgard::truncated_aes(3, Padding::LengthBigEndian);
+        let collision = find_cascade_collision(&f, &g);
+        assert_ne!(collision.first, collision.second);
+        assert_eq!(f.hash(&collision.first), f.hash(&collision.second));
+        assert_eq!(g.hash(&collision.first), g.hash(&collision.second));
+
+        // A generic birthday attack on the 40 bits f || g would need about 2^20 calls.
+        println!(
+            "f calls: {}, g calls: {}, expected around: {} and {}",
+            collision.f_calls,
+            collision.g_calls,
+            12 * (1 << 8),
+            1 << 13
+        );
+        assert!(collision.f_calls + collision.g_calls < 1 << 20);
+    }
+}
@@ -0,0 +1,140 @@
+//! Kelsey–Schneier expandable messages and the long message second preimage attack.
+//! https://cryptopals.com/sets/7/challenges/53
+//!
+//! Finding a second preimage for a b bits hash should cost 2^b. But a long message of 2^k blocks
+//! goes through 2^k intermediate states, and hitting any of them is enough: it costs 2^(b-k).
+//! The catch is the length padding (Merkle–Damgård strengthening): the forged message must have
+//! the same length as the original, so the prefix leading to the hit state must have the right
+//! number of blocks. An expandable message solves it: k collisions between a 1 block message and
+//! a 2^i + 1 blocks message, for i in 0..k. Choosing the short or the long side of each
+//! collision gives messages of any length between k and k + 2^k - 1 blocks, all with the same
+//! final state.
+
+use crate::hash::merkle_damgard::multicollision::{find_collision_from, random_block};
+use crate::hash::merkle_damgard::MerkleDamgard;
+use std::collections::HashMap;
+
+/// A set of messages of any length between k and k + 2^k - 1 blocks, leading to the same state.
+#[derive(Debug, Clone)]
+pub struct ExpandableMessage {
+    /// The i-th pair holds a 1 block message and a colliding 2^(k-1-i) + 1 blocks message.
+    pub pairs: Vec<(Vec<u8>, Vec<u8>)>,
+    /// The state all the messages lead to.
+    pub state: Vec<u8>,
+}
+
+impl ExpandableMessage {
+    pub fn generate(hash: &MerkleDamgard, state: &[u8], k: usize) -> Self {
+        let dummy = vec![0u8; hash.block_size()];
+        let mut pairs = vec![];
+        let mut state = state.to_vec();
+        for i in (0..k).rev() {
+            // The long message is 2^i dummy blocks, plus a block colliding with the short one.
+            let dummies = dummy.repeat(1 << i);
+            let dummies_state = hash.iterate(&state, &dummies);
+            let (short, last, next_state) = find_collision_from(hash, &state, &dummies_state);
+            let mut long = dummies;
+            long.extend(last);
+            pairs.push((short, long));
+            state = next_state;
+        }
+        Self { pairs, state }
+    }
+
+    pub fn min_blocks(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn max_blocks(&self) -> usize {
+        self.pairs.len() + (1 << self.pairs.len()) - 1
+    }
+
+    /// The message of exactly `blocks` blocks.
+    pub fn message(&self, blocks: usize) -> Vec<u8> {
+        assert!((self.min_blocks()..=self.max_blocks()).contains(&blocks));
+        let k = self.pairs.len();
+        // Each long message adds 2^i blocks: use the binary representation of the extra blocks.
+        let extra = blocks - k;
+        self.pairs
+            .iter()
+            .enumerate()
+            .flat_map(|(index, (short, long))| {
+                let i = k - 1 - index;
+                if extra >> i & 1 == 1 {
+                    long.clone()
+                } else {
+                    short.clone()
+                }
+            })
+            .collect()
+    }
+}
+
+/// Finds a different message with the same hash as `message`, which has to be made of full
+/// blocks. The longer the message, the cheaper the attack.
+/// Returns `None` if the message is shorter than 2 blocks.
+pub fn find_second_preimage(hash: &MerkleDamgard, message: &[u8]) -> Option<Vec<u8>> {
+    let block_size = hash.block_size();
+    assert_eq!(message.len() % block_size, 0);
+    let blocks = message.len() / block_size;
+    if blocks < 2 {
+        return None;
+    }
+    let k = blocks.ilog2() as usize;
+    let expandable = ExpandableMessage::generate(hash, hash.iv(), k);
+
+    // The state after the i-th block of the original message, for the positions the bridge
+    // block can take: after an expandable message of i blocks.
+    let mut intermediate = HashMap::new();
+    let mut state = hash.iv().to_vec();
+    for (i, block) in message.chunks(block_size).enumerate() {
+        state = hash.compress(&state, block);
+        if (expandable.min_blocks()..=expandable.max_blocks()).contains(&i) {
+            intermediate.entry(state.clone()).or_insert(i);
+        }
+    }
+
+    loop {
+        let bridge = random_block(hash);
+        let bridge_state = hash.compress(&expandable.state, &bridge);
+        if let Some(&i) = intermediate.get(&bridge_state) {
+            let mut forged = expandable.message(i);
+            forged.extend(bridge);
+            forged.extend_from_slice(&message[(i + 1) * block_size..]);
+            if forged != message {
+                return Some(forged);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::merkle_damgard::second_preimage::{find_second_preimage, ExpandableMessage};
+    use crate::hash::merkle_damgard::{MerkleDamgard, Padding};
+    use rand::Rng;
+
+    #[test]
+    fn test_expandable_message() {
+        let hash = MerkleDamgard::truncated_aes(2, Padding::LengthBigEndian);
+        let expandable = ExpandableMessage::generate(&hash, hash.iv(), 4);
+        assert_eq!((expandable.min_blocks(), expandable.max_blocks()), (4, 19));
+        for blocks in 4..=19 {
+            let message = expandable.message(blocks);
+            assert_eq!(message.len(), blocks * 16);
+            assert_eq!(hash.iterate(hash.iv(), &message), expandable.state);
+        }
+    }
+
+    #[test]
+    fn test_find_second_preimage() {
+        let hash = MerkleDamgard::truncated_aes(3, Padding::LengthBigEndian);
+        let k = 10;
+        let message: Vec<u8> = (0..16 << k).map(|_| rand::thread_rng().gen()).collect();
+        let forged = find_second_preimage(&hash, &message).unwrap();
+        assert_ne!(forged, message);
+        assert_eq!(forged.len(), message.len());
+        assert_eq!(hash.hash(&forged), hash.hash(&message));
+        println!("Compression function calls: {}", hash.compression_calls());
+    }
+}
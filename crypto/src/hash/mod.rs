@@ -1,5 +1,10 @@
+pub mod blake2;
+pub mod collision;
 pub mod md4;
+pub mod merkle_damgard;
+pub mod rainbow;
 pub mod sha1;
+pub mod sha3;
 
 pub fn to_hex<T: AsRef<[u8]>>(input: T) -> String {
     let mut s = String::new();
@@ -0,0 +1,303 @@
+//! Rainbow tables: a time-memory tradeoff to invert a one-way function on a small set of secrets,
+//! e.g. 16 bits MT19937 seeds or short passwords.
+//!
+//! A chain starts from a secret and alternates the hash and a reduction function, mapping the
+//! digest back to a secret. Only the start and the end of each chain are stored. To invert a
+//! digest we guess its column in the chain, walk to the end of the chain and look the end up:
+//! if it's there, we walk the chain again from its start to find the secret.
+//! A different reduction for each column (the "rainbow") keeps chains from merging unless they
+//! hit the same value in the same column.
+//! https://lasec.epfl.ch/pub/lasec/doc/Oech03.pdf
+
+use std::convert::{TryFrom, TryInto};
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 4] = b"RBT2";
+/// The largest secrets a saved table can have, far more than a rainbow table is useful for.
+pub const MAX_SECRET_SIZE: usize = 1 << 16;
+
+/// A rainbow table over the secrets of `secret_size` bytes.
+/// `hash` is the function to invert, `reduce(digest, column)` maps a digest to a secret.
+pub struct RainbowTable<H, R> {
+    hash: H,
+    reduce: R,
+    secret_size: usize,
+    chain_length: u32,
+    /// (start, end) of each chain, sorted by end.
+    chains: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl<H, R> RainbowTable<H, R>
+where
+    H: Fn(&[u8]) -> Vec<u8>,
+    R: Fn(&[u8], u32) -> Vec<u8>,
+{
+    /// Builds the table with a chain from each of the `starts`. Chains ending in the same
+    /// secret are redundant, only the first one is kept.
+    pub fn build(
+        hash: H,
+        reduce: R,
+        chain_length: u32,
+        starts: impl IntoIterator<Item = Vec<u8>>,
+    ) -> Self {
+        let mut chains: Vec<(Vec<u8>, Vec<u8>)> = vec![];
+        let mut secret_size = 0;
+        for start in starts {
+            secret_size = start.len();
+            let mut secret = start.clone();
+            for column in 0..chain_length {
+                secret = reduce(&hash(&secret), column);
+            }
+            chains.push((start, secret));
+        }
+        assert!(chains.iter().all(|(start, _)| start.len() == secret_size));
+        chains.sort_by(|a, b| a.1.cmp(&b.1));
+        chains.dedup_by(|a, b| a.1 == b.1);
+        Self {
+            hash,
+            reduce,
+            secret_size,
+            chain_length,
+            chains,
+        }
+    }
+
+    pub fn chain_count(&self) -> usize {
+        self.chains.len()
+    }
+
+    pub fn chain_length(&self) -> u32 {
+        self.chain_length
+    }
+
+    /// Returns a secret whose hash is `digest`, if the table covers it.
+    /// It costs up to chain_length^2 / 2 hashes.
+    pub fn lookup(&self, digest: &[u8]) -> Option<Vec<u8>> {
+        for column in (0..self.chain_length).rev() {
+            let mut end = (self.reduce)(digest, column);
+            for next_column in column + 1..self.chain_length {
+                end = (self.reduce)(&(self.hash)(&end), next_column);
+            }
+            let first = self.chains.partition_point(|(_, e)| *e < end);
+            for (start, _) in self.chains[first..].iter().take_while(|(_, e)| *e == end) {
+                // The end can match by a merge in a later column: a false alarm.
+                if let Some(secret) = self.walk(start, column, digest) {
+                    return Some(secret);
+                }
+            }
+        }
+        None
+    }
+
+    /// Walks the chain from `start` looking for `digest` in `column`.
+    fn walk(&self, start: &[u8], column: u32, digest: &[u8]) -> Option<Vec<u8>> {
+        let mut secret = start.to_vec();
+        for c in 0..column {
+            secret = (self.reduce)(&(self.hash)(&secret), c);
+        }
+        if (self.hash)(&secret) == digest {
+            Some(secret)
+        } else {
+            None
+        }
+    }
+
+    /// Writes the table: a magic, the secret size, the chain length and the number of chains
+    /// (u32 little endian), then the start and the end of each chain. The secrets must have
+    /// between 1 and `MAX_SECRET_SIZE` bytes.
+    pub fn save(&self, writer: &mut impl Write) -> io::Result<()> {
+        if !(1..=MAX_SECRET_SIZE).contains(&self.secret_size) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid secret size",
+            ));
+        }
+        let too_large = |_| io::Error::new(io::ErrorKind::InvalidInput, "table too large");
+        let secret_size = u32::try_from(self.secret_size).map_err(too_large)?;
+        let chain_count = u32::try_from(self.chains.len()).map_err(too_large)?;
+        writer.write_all(MAGIC)?;
+        writer.write_all(&secret_size.to_le_bytes())?;
+        writer.write_all(&self.chain_length.to_le_bytes())?;
+        writer.write_all(&chain_count.to_le_bytes())?;
+        for (start, end) in &self.chains {
+            writer.write_all(start)?;
+            writer.write_all(end)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a table written by `save`. The hash and the reduction aren't saved, they must be
+    /// the ones the table was built with.
+    pub fn load(hash: H, reduce: R, reader: &mut impl Read) -> io::Result<Self> {
+        let mut header = [0u8; 16];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a rainbow table",
+            ));
+        }
+        let secret_size = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+        let chain_length = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        let chain_count = u32::from_le_bytes(header[12..16].try_into().unwrap());
+        if !(1..=MAX_SECRET_SIZE).contains(&secret_size) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid secret size",
+            ));
+        }
+        // The header isn't trusted: the chains are allocated as they are read, a truncated file
+        // fails on the first missing chain.
+        let mut chains = vec![];
+        for _ in 0..chain_count {
+            let mut start = vec![0u8; secret_size];
+            let mut end = vec![0u8; secret_size];
+            reader.read_exact(&mut start)?;
+            reader.read_exact(&mut end)?;
+            chains.push((start, end));
+        }
+        Ok(Self {
+            hash,
+            reduce,
+            secret_size,
+            chain_length,
+            chains,
+        })
+    }
+}
+
+/// A reduction to `secret_size` bytes secrets: the first bytes of the digest, xored with the
+/// column.
+pub fn truncating_reduction(secret_size: usize) -> impl Fn(&[u8], u32) -> Vec<u8> {
+    move |digest, column| {
+        let column = column.to_le_bytes();
+        digest[..secret_size]
+            .iter()
+            .enumerate()
+            .map(|(i, b)| b ^ column.get(i).unwrap_or(&0))
+            .collect()
+    }
+}
+
+/// A reduction to passwords of `length` characters from `charset`: the first 8 bytes of the
+/// digest plus the column, written in base `charset.len()`.
+pub fn charset_reduction(charset: &[u8], length: usize) -> impl Fn(&[u8], u32) -> Vec<u8> + '_ {
+    move |digest, column| {
+        let mut n = u64::from_be_bytes(digest[..8].try_into().unwrap()).wrapping_add(column as u64);
+        (0..length)
+            .map(|_| {
+                let c = charset[(n % charset.len() as u64) as usize];
+                n /= charset.len() as u64;
+                c
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::rainbow::{charset_reduction, truncating_reduction, RainbowTable};
+    use crate::hash::sha1::sha1;
+    use crate::random::Mt19937MersenneTwisterRng;
+    use std::convert::TryInto;
+
+    /// The first output of MT19937 seeded with a 16 bits seed.
+    fn mt19937_first_output(seed: &[u8]) -> Vec<u8> {
+        let seed = u16::from_be_bytes(seed.try_into().unwrap());
+        let mut rng = Mt19937MersenneTwisterRng::new_seed(seed as u32);
+        rng.extract_number().to_be_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_mt19937_seeds() {
+        let table = RainbowTable::build(
+            mt19937_first_output,
+            truncating_reduction(2),
+            32,
+            (0..4096u16).map(|i| (i * 16).to_be_bytes().to_vec()),
+        );
+        let mut found = 0;
+        for seed in (0..=u16::MAX).step_by(1021) {
+            let digest = mt19937_first_output(&seed.to_be_bytes());
+            if let Some(secret) = table.lookup(&digest) {
+                assert_eq!(mt19937_first_output(&secret), digest);
+                found += 1;
+            }
+        }
+        println!("Chains: {}, found: {}/65", table.chain_count(), found);
+        // A single table can't cover everything: merging chains are dropped.
+        assert!(found > 65 / 2);
+    }
+
+    #[test]
+    fn test_passwords_and_storage() {
+        const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+        let table = RainbowTable::build(
+            sha1,
+            charset_reduction(CHARSET, 3),
+            32,
+            (0..4096u32).map(|i| charset_reduction(CHARSET, 3)(&sha1(&i.to_be_bytes()), 0)),
+        );
+        let mut file = vec![];
+        table.save(&mut file).unwrap();
+        assert_eq!(file.len(), 16 + table.chain_count() * 6);
+        let loaded =
+            RainbowTable::load(sha1, charset_reduction(CHARSET, 3), &mut file.as_slice()).unwrap();
+        assert_eq!(loaded.chain_count(), table.chain_count());
+        assert_eq!(loaded.chain_length(), 32);
+
+        let mut found = 0;
+        for password in [
+            b"abc", b"cat", b"dog", b"zzz", b"xyz", b"sun", b"sky", b"red",
+        ] {
+            if let Some(secret) = loaded.lookup(&sha1(password)) {
+                assert_eq!(sha1(&secret), sha1(password));
+                found += 1;
+            }
+        }
+        println!("Chains: {}, found: {}/8", loaded.chain_count(), found);
+        assert!(found >= 4);
+
+        assert!(
+            RainbowTable::load(sha1, charset_reduction(CHARSET, 3), &mut &b"nope"[..]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_large_secrets_storage() {
+        // Secrets longer than 255 bytes.
+        let hash = |secret: &[u8]| secret.iter().map(|b| b.wrapping_mul(3)).collect();
+        let reduce = |digest: &[u8], column: u32| digest.iter().map(|b| b ^ column as u8).collect();
+        let starts: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i; 300]).collect();
+        let table = RainbowTable::build(hash, reduce, 4, starts.clone());
+        let mut file = vec![];
+        table.save(&mut file).unwrap();
+        let loaded = RainbowTable::load(hash, reduce, &mut file.as_slice()).unwrap();
+        assert_eq!(loaded.chain_count(), 4);
+        for start in &starts {
+            assert_eq!(loaded.lookup(&hash(start)).as_ref(), Some(start));
+        }
+    }
+
+    #[test]
+    fn test_load_untrusted_header() {
+        let load =
+            |file: &[u8]| RainbowTable::load(sha1, truncating_reduction(2), &mut &file[..]).err();
+        let header = |secret_size: u32, chain_count: u32| {
+            [
+                &b"RBT2"[..],
+                &secret_size.to_le_bytes(),
+                &32u32.to_le_bytes(),
+                &chain_count.to_le_bytes(),
+            ]
+            .concat()
+        };
+        // Claims 4G chains, but has none.
+        let error = load(&header(2, u32::MAX)).unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+        for secret_size in [0, u32::MAX] {
+            let error = load(&header(secret_size, 1)).unwrap();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        }
+    }
+}
@@ -33,6 +33,10 @@ impl Sha1State {
         let h4 = u32::from_be_bytes(message_digest[16..20].try_into().unwrap());
         Self { h0, h1, h2, h3, h4 }
     }
+    /// The message digest for this state, the inverse of `from_message_digset`.
+    pub fn to_message_digest(&self) -> Vec<u8> {
+        self.iter().flat_map(|h| h.to_be_bytes()).collect()
+    }
     fn new() -> Self {
         Self {
             h0: H0,
@@ -49,7 +53,7 @@ impl Sha1State {
     }
 }
 
-fn process_block(mut state: Sha1State, block: &[u8]) -> Sha1State {
+pub(crate) fn process_block(mut state: Sha1State, block: &[u8]) -> Sha1State {
     assert_eq!(block.len(), BLOCK_SIZE);
 
     let mut w = [0u32; 80];
@@ -100,7 +104,8 @@ fn process_block(mut state: Sha1State, block: &[u8]) -> Sha1State {
 }
 pub fn sha1_padding_needed(message_size: usize) -> usize {
     const SIZE_OF_ONE: usize = mem::size_of::<u8>();
-    BLOCK_SIZE - (message_size + SIZE_OF_ONE + mem::size_of::<u64>()) % BLOCK_SIZE
+    // The modulo keeps a message ending exactly on the block boundary from getting a full block.
+    (BLOCK_SIZE - (message_size + SIZE_OF_ONE + mem::size_of::<u64>()) % BLOCK_SIZE) % BLOCK_SIZE
 }
 
 /// Produce a sha1 hash of payload.
@@ -143,11 +148,7 @@ pub fn sha1_state_len(
         state = process_block(state, block);
     }
 
-    let mut ret = [0u8; 20];
-    for (i, h) in state.iter().enumerate() {
-        ret[4 * i..4 * i + 4].copy_from_slice(&h.to_be_bytes());
-    }
-    (ret.to_vec(), state)
+    (state.to_message_digest(), state)
 }
 
 #[cfg(test)]
@@ -182,6 +183,11 @@ mod test {
                 r#"The attack on secret-prefix SHA1 relies on the fact that you can take the ouput of SHA-1 and use it as a new starting point for SHA-1, thus taking an arbitrary SHA-1 hash and "feeding it more data"."#,
                 "83bd0a05c761efdf84eac56ad4afd91fdef620e8",
             ),
+            // The padding exactly fills the block.
+            (
+                "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "c1c8bbdc22796e28c0e15163d20899b65621d65a",
+            ),
         ];
         for (input, expected) in tests {
             let received = to_hex(&sha1(input.as_bytes()));
@@ -0,0 +1,303 @@
+//! SHA-3 and SHAKE, built on the Keccak-f[1600] sponge.
+//! https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+//!
+//! Unlike sha1 and md4, the digest is only a small part of the internal state: the "capacity"
+//! lanes are never output. Loading a digest back into a state (the trick behind the set_4 length
+//! extension attacks) leaves the capacity at zero, so extending it produces garbage.
+
+use std::convert::TryInto;
+
+const ROUNDS: usize = 24;
+const STATE_SIZE: usize = 200; // 1600 bits.
+
+/// Domain separation suffixes, already combined with the first bit of the pad10*1 padding.
+const SHA3_SUFFIX: u8 = 0x06;
+const SHAKE_SUFFIX: u8 = 0x1f;
+
+const ROUND_CONSTANTS: [u64; ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets for the rho step, indexed by x + 5 * y.
+const RHO_OFFSETS: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// The 5x5 matrix of 64 bit lanes. Lane (x, y) is stored at index x + 5 * y.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Sha3State {
+    lanes: [u64; 25],
+}
+impl Default for Sha3State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Sha3State {
+    /// Function to generate a state from a message digest, the same way `Sha1State` does.
+    /// The digest fills the first lanes, every other lane is unknown and left at zero.
+    pub fn from_message_digest(message_digest: &[u8]) -> Self {
+        let mut bytes = [0u8; STATE_SIZE];
+        bytes[..message_digest.len()].copy_from_slice(message_digest);
+        Self::from_bytes(&bytes)
+    }
+    fn new() -> Self {
+        Self { lanes: [0; 25] }
+    }
+    fn from_bytes(bytes: &[u8; STATE_SIZE]) -> Self {
+        let mut lanes = [0u64; 25];
+        for (lane, chunk) in lanes.iter_mut().zip(bytes.chunks_exact(8)) {
+            *lane = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Self { lanes }
+    }
+    fn to_bytes(&self) -> [u8; STATE_SIZE] {
+        let mut bytes = [0u8; STATE_SIZE];
+        for (chunk, lane) in bytes.chunks_exact_mut(8).zip(self.lanes.iter()) {
+            chunk.copy_from_slice(&lane.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+/// The Keccak-f[1600] permutation.
+pub fn keccak_f(lanes: &mut [u64; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        // θ step
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = lanes[x] ^ lanes[x + 5] ^ lanes[x + 10] ^ lanes[x + 15] ^ lanes[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                lanes[x + 5 * y] ^= d;
+            }
+        }
+        // ρ and π steps: B[y, 2x + 3y] = rot(A[x, y], r[x, y])
+        let mut b = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] =
+                    lanes[x + 5 * y].rotate_left(RHO_OFFSETS[x + 5 * y]);
+            }
+        }
+        // χ step
+        for x in 0..5 {
+            for y in 0..5 {
+                lanes[x + 5 * y] =
+                    b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+        // ι step
+        lanes[0] ^= round_constant;
+    }
+}
+
+/// Xor a rate-sized block into the state and permute it.
+fn process_block(mut state: Sha3State, block: &[u8]) -> Sha3State {
+    for (lane, chunk) in state.lanes.iter_mut().zip(block.chunks_exact(8)) {
+        *lane ^= u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    keccak_f(&mut state.lanes);
+    state
+}
+
+/// Number of bytes of pad10*1 appended to a message of `message_size` bytes. Always at least one.
+pub fn sha3_padding_needed(message_size: usize, rate: usize) -> usize {
+    rate - message_size % rate
+}
+
+/// Absorbs `payload` into `state` and squeezes `output_len` bytes out of the sponge.
+/// `payload_length` is the length of the whole message, including whatever was absorbed to
+/// produce `state`. It's only used to place the padding, as Keccak doesn't encode the length.
+pub fn keccak_state_len(
+    state: Sha3State,
+    payload: &[u8],
+    payload_length: usize,
+    rate: usize,
+    suffix: u8,
+    output_len: usize,
+) -> (Vec<u8>, Sha3State) {
+    let mut payload = payload.to_vec();
+    let padding_needed = sha3_padding_needed(payload_length, rate);
+    payload.push(suffix);
+    payload.extend(vec![0; padding_needed - 1]);
+    *payload.last_mut().unwrap() |= 0x80;
+
+    let mut state = state;
+    // Absorbing phase.
+    for block in payload.chunks(rate) {
+        state = process_block(state, block);
+    }
+
+    // Squeezing phase.
+    let mut output = state.to_bytes()[..rate].to_vec();
+    let mut squeezed = state.clone();
+    while output.len() < output_len {
+        keccak_f(&mut squeezed.lanes);
+        output.extend_from_slice(&squeezed.to_bytes()[..rate]);
+    }
+    output.truncate(output_len);
+    (output, state)
+}
+
+fn sha3(payload: &[u8], output_len: usize) -> Vec<u8> {
+    let rate = STATE_SIZE - 2 * output_len;
+    keccak_state_len(
+        Sha3State::new(),
+        payload,
+        payload.len(),
+        rate,
+        SHA3_SUFFIX,
+        output_len,
+    )
+    .0
+}
+
+pub fn sha3_224(payload: &[u8]) -> Vec<u8> {
+    sha3(payload, 28)
+}
+
+pub fn sha3_256(payload: &[u8]) -> Vec<u8> {
+    sha3(payload, 32)
+}
+
+pub fn sha3_384(payload: &[u8]) -> Vec<u8> {
+    sha3(payload, 48)
+}
+
+pub fn sha3_512(payload: &[u8]) -> Vec<u8> {
+    sha3(payload, 64)
+}
+
+/// Produce a sha3-256 hash of payload, starting from the provided state.
+/// Same shape as `sha1_state_len`, used to show that length extension doesn't work on SHA-3.
+pub fn sha3_256_state_len(
+    state: Sha3State,
+    payload: &[u8],
+    payload_length: usize,
+) -> (Vec<u8>, Sha3State) {
+    keccak_state_len(state, payload, payload_length, 136, SHA3_SUFFIX, 32)
+}
+
+/// SHAKE128 extendable-output function.
+pub fn shake128(payload: &[u8], output_len: usize) -> Vec<u8> {
+    keccak_state_len(
+        Sha3State::new(),
+        payload,
+        payload.len(),
+        168,
+        SHAKE_SUFFIX,
+        output_len,
+    )
+    .0
+}
+
+/// SHAKE256 extendable-output function.
+pub fn shake256(payload: &[u8], output_len: usize) -> Vec<u8> {
+    keccak_state_len(
+        Sha3State::new(),
+        payload,
+        payload.len(),
+        136,
+        SHAKE_SUFFIX,
+        output_len,
+    )
+    .0
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::sha3::{sha3_224, sha3_256, sha3_384, sha3_512, shake128, shake256};
+    use crate::hash::to_hex;
+    use std::assert_eq;
+
+    #[test]
+    fn test_sha3() {
+        // NIST examples: empty message, "abc" and 1600 bits of 0xa3.
+        let long = vec![0xa3; 200];
+        let tests: [(&[u8], [&str; 4]); 3] = [
+            (
+                b"",
+                [
+                    "6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7",
+                    "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+                    "0c63a75b845e4f7d01107d852e4c2485c51a50aaaa94fc61995e71bbee983a2ac3713831264adb47fb6bd1e058d5f004",
+                    "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a615b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26",
+                ],
+            ),
+            (
+                b"abc",
+                [
+                    "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf",
+                    "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+                    "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25",
+                    "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+                ],
+            ),
+            (
+                &long,
+                [
+                    "9376816aba503f72f96ce7eb65ac095deee3be4bf9bbc2a1cb7e11e0",
+                    "79f38adec5c20307a98ef76e8324afbfd46cfd81b22e3973c65fa1bd9de31787",
+                    "1881de2ca7e41ef95dc4732b8f5f002b189cc1e42b74168ed1732649ce1dbcdd76197a31fd55ee989f2d7050dd473e8f",
+                    "e76dfad22084a8b1467fcf2ffa58361bec7628edf5f3fdc0e4805dc48caeeca81b7c13c30adf52a3659584739a2df46be589c51ca1a4a8416df6545a1ce8ba00",
+                ],
+            ),
+        ];
+        for (input, [expected_224, expected_256, expected_384, expected_512]) in tests {
+            assert_eq!(to_hex(sha3_224(input)), expected_224);
+            assert_eq!(to_hex(sha3_256(input)), expected_256);
+            assert_eq!(to_hex(sha3_384(input)), expected_384);
+            assert_eq!(to_hex(sha3_512(input)), expected_512);
+        }
+    }
+
+    #[test]
+    fn test_shake() {
+        let tests: [(&[u8], &str, &str); 2] = [
+            (
+                b"",
+                "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26",
+                "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762fd75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be",
+            ),
+            (
+                b"abc",
+                "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc8",
+                "483366601360a8771c6863080cc4114d8db44530f8f1e1ee4f94ea37e78b5739d5a15bef186a5386c75744c0527e1faa9f8726e462a12a4feb06bd8801e751e4",
+            ),
+        ];
+        for (input, expected_128, expected_256) in tests {
+            assert_eq!(to_hex(shake128(input, 32)), expected_128);
+            assert_eq!(to_hex(shake256(input, 64)), expected_256);
+        }
+        // Longer outputs need more than one squeeze, and are a prefix-extension of shorter ones.
+        let long = shake128(b"", 500);
+        assert_eq!(long.len(), 500);
+        assert_eq!(long[..32], shake128(b"", 32)[..]);
+    }
+}
@@ -0,0 +1,107 @@
+//! HMAC-based Extract-and-Expand Key Derivation Function (HKDF)
+//! https://www.rfc-editor.org/rfc/rfc5869
+//!
+//! HKDF first "extracts" a fixed-length pseudorandom key (PRK) from the input keying material,
+//! then "expands" it into as many output bytes as needed. The `info` parameter binds the output
+//! to a context, so the same shared secret can yield independent encryption and MAC keys.
+
+use crate::mac::hmac_sha1;
+
+const HASH_LEN: usize = 20; // sha1 output size.
+
+/// HKDF-Extract(salt, IKM) -> PRK
+/// If salt is not provided, it is set to a string of HashLen zeros.
+pub fn hkdf_sha1_extract(salt: &[u8], ikm: &[u8]) -> Vec<u8> {
+    if salt.is_empty() {
+        hmac_sha1(&[0u8; HASH_LEN], ikm)
+    } else {
+        hmac_sha1(salt, ikm)
+    }
+}
+
+/// HKDF-Expand(PRK, info, L) -> OKM
+/// Returns `None` if the requested length is bigger than 255*HashLen.
+pub fn hkdf_sha1_expand(prk: &[u8], info: &[u8], length: usize) -> Option<Vec<u8>> {
+    if length > 255 * HASH_LEN {
+        return None;
+    }
+    let mut okm = Vec::with_capacity(length);
+    // T(0) = empty string
+    let mut t = vec![];
+    let mut counter = 1u8;
+    while okm.len() < length {
+        // T(i) = HMAC-Hash(PRK, T(i-1) | info | i)
+        let mut input = t;
+        input.extend_from_slice(info);
+        input.push(counter);
+        t = hmac_sha1(prk, &input);
+        okm.extend_from_slice(&t);
+        counter = counter.wrapping_add(1);
+    }
+    okm.truncate(length);
+    Some(okm)
+}
+
+/// Extract and then expand `length` bytes of output keying material.
+pub fn hkdf_sha1(salt: &[u8], ikm: &[u8], info: &[u8], length: usize) -> Option<Vec<u8>> {
+    hkdf_sha1_expand(&hkdf_sha1_extract(salt, ikm), info, length)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::from_hex;
+    use crate::hash::to_hex;
+    use crate::kdf::{hkdf_sha1, hkdf_sha1_expand, hkdf_sha1_extract};
+
+    #[test]
+    fn test_hkdf_sha1() {
+        // RFC 5869, Appendix A.4 - A.7
+        let tests = [
+            (
+                "0b0b0b0b0b0b0b0b0b0b0b",
+                "000102030405060708090a0b0c",
+                "f0f1f2f3f4f5f6f7f8f9",
+                42,
+                "9b6c18c432a7bf8f0e71c8eb88f4b30baa2ba243",
+                "085a01ea1b10f36933068b56efa5ad81a4f14b822f5b091568a9cdd4f155fda2c22e422478d305f3f896",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f404142434445464748494a4b4c4d4e4f",
+                "606162636465666768696a6b6c6d6e6f707172737475767778797a7b7c7d7e7f808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9fa0a1a2a3a4a5a6a7a8a9aaabacadaeaf",
+                "b0b1b2b3b4b5b6b7b8b9babbbcbdbebfc0c1c2c3c4c5c6c7c8c9cacbcccdcecfd0d1d2d3d4d5d6d7d8d9dadbdcdddedfe0e1e2e3e4e5e6e7e8e9eaebecedeeeff0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+                82,
+                "8adae09a2a307059478d309b26c4115a224cfaf6",
+                "0bd770a74d1160f7c9f12cd5912a06ebff6adcae899d92191fe4305673ba2ffe8fa3f1a4e5ad79f3f334b3b202b2173c486ea37ce3d397ed034c7f9dfeb15c5e927336d0441f4c4300e2cff0d0900b52d3b4",
+            ),
+            (
+                "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+                "",
+                "",
+                42,
+                "da8c8a73c7fa77288ec6f5e7c297786aa0d32d01",
+                "0ac1af7002b3d761d1e55298da9d0506b9ae52057220a306e07b6b87e8df21d0ea00033de03984d34918",
+            ),
+            (
+                "0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c",
+                "",
+                "",
+                42,
+                "2adccada18779e7c2077ad2eb19d3f3e731385dd",
+                "2c91117204d745f3500d636a62f64f0ab3bae548aa53d423b0d1f27ebba6f5e5673a081d70cce7acfc48",
+            ),
+        ];
+        for (ikm, salt, info, length, prk, okm) in tests {
+            let ikm = from_hex(ikm).unwrap();
+            let salt = from_hex(salt).unwrap();
+            let info = from_hex(info).unwrap();
+            assert_eq!(to_hex(hkdf_sha1_extract(&salt, &ikm)), prk);
+            assert_eq!(to_hex(hkdf_sha1(&salt, &ikm, &info, length).unwrap()), okm);
+        }
+    }
+
+    #[test]
+    fn test_hkdf_sha1_expand_too_long() {
+        assert!(hkdf_sha1_expand(&[0u8; 20], b"", 255 * 20).is_some());
+        assert!(hkdf_sha1_expand(&[0u8; 20], b"", 255 * 20 + 1).is_none());
+    }
+}
@@ -0,0 +1,5 @@
+mod hkdf;
+mod pbkdf2;
+
+pub use hkdf::*;
+pub use pbkdf2::*;
@@ -0,0 +1,88 @@
+//! Password-Based Key Derivation Function 2 (PBKDF2)
+//! https://www.rfc-editor.org/rfc/rfc8018#section-5.2
+//!
+//! The password is run through the PRF `iterations` times, so that brute forcing a low entropy
+//! password gets proportionally more expensive. The salt prevents precomputed tables.
+
+use crate::mac::hmac_sha1;
+
+const HASH_LEN: usize = 20; // sha1 output size.
+
+/// PBKDF2 (P, S, c, dkLen) using HMAC-SHA1 as the underlying pseudorandom function.
+pub fn pbkdf2_hmac_sha1(password: &[u8], salt: &[u8], iterations: u32, dk_len: usize) -> Vec<u8> {
+    assert!(iterations > 0, "iterations must be a positive integer");
+    let blocks = dk_len.div_ceil(HASH_LEN);
+    let mut derived_key = Vec::with_capacity(blocks * HASH_LEN);
+    for block_index in 1..=blocks as u32 {
+        derived_key.extend(pbkdf2_block(password, salt, iterations, block_index));
+    }
+    derived_key.truncate(dk_len);
+    derived_key
+}
+
+/// F (P, S, c, i) = U_1 \xor U_2 \xor ... \xor U_c
+/// where U_1 = PRF (P, S || INT (i)) and U_j = PRF (P, U_{j-1}).
+fn pbkdf2_block(password: &[u8], salt: &[u8], iterations: u32, block_index: u32) -> Vec<u8> {
+    let mut input = salt.to_vec();
+    input.extend_from_slice(&block_index.to_be_bytes());
+    let mut u = hmac_sha1(password, &input);
+    let mut block = u.clone();
+    for _ in 1..iterations {
+        u = hmac_sha1(password, &u);
+        block.iter_mut().zip(u.iter()).for_each(|(b, u)| *b ^= u);
+    }
+    block
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::to_hex;
+    use crate::kdf::pbkdf2_hmac_sha1;
+
+    #[test]
+    fn test_pbkdf2_hmac_sha1() {
+        // RFC 6070 test vectors. The 16777216 iterations one is skipped, it's too slow.
+        let tests = [
+            (
+                "password",
+                "salt",
+                1,
+                20,
+                "0c60c80f961f0e71f3a9b524af6012062fe037a6",
+            ),
+            (
+                "password",
+                "salt",
+                2,
+                20,
+                "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957",
+            ),
+            (
+                "password",
+                "salt",
+                4096,
+                20,
+                "4b007901b765489abead49d926f721d065a429c1",
+            ),
+            (
+                "passwordPASSWORDpassword",
+                "saltSALTsaltSALTsaltSALTsaltSALTsalt",
+                4096,
+                25,
+                "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038",
+            ),
+            (
+                "pass\0word",
+                "sa\0lt",
+                4096,
+                16,
+                "56fa6aa75548099dcc37d7f03425e0c3",
+            ),
+        ];
+        for (password, salt, iterations, dk_len, expected) in tests {
+            let received =
+                pbkdf2_hmac_sha1(password.as_bytes(), salt.as_bytes(), iterations, dk_len);
+            assert_eq!(to_hex(received), expected, "Failed on input: {}", password);
+        }
+    }
+}
@@ -6,6 +6,7 @@ pub use pkcs7::Pkcs7;
 pub mod aes;
 mod cryptobreak;
 pub mod hash;
+pub mod kdf;
 pub mod mac;
 mod pkcs7;
 pub mod random;
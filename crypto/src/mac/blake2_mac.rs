@@ -0,0 +1,47 @@
+use crate::hash::blake2::{blake2b_params, blake2s_params};
+
+/// BLAKE2b in keyed mode. Unlike `sha1_mac`, prepending the key is secure here: the key gets its
+/// own block, so there's no need for the HMAC construction.
+pub fn blake2b_mac(key: &[u8], payload: &[u8]) -> Vec<u8> {
+    blake2b_params(payload, 64, key, &[], &[])
+}
+
+/// BLAKE2s in keyed mode.
+pub fn blake2s_mac(key: &[u8], payload: &[u8]) -> Vec<u8> {
+    blake2s_params(payload, 32, key, &[], &[])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::to_hex;
+    use crate::mac::{blake2b_mac, blake2s_mac};
+    use std::assert_eq;
+
+    #[test]
+    fn test_blake2_mac() {
+        // From the BLAKE2 reference keyed KAT: key = 00 01 02 .., message = 00 01 02 ..
+        let key: Vec<u8> = (0..64).collect();
+        let message: Vec<u8> = (0..255).collect();
+        let tests = [
+            (
+                blake2b_mac(&key, &message),
+                "142709d62e28fcccd0af97fad0f8465b971e82201dc51070faa0372aa43e92484be1c1e73ba10906d5d1853db6a4106e0a7bf9800d373d6dee2d46d62ef2a461",
+            ),
+            (
+                blake2b_mac(&key, b""),
+                "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568",
+            ),
+            (
+                blake2s_mac(&key[..32], &message),
+                "3fb735061abc519dfe979e54c1ee5bfad0a9d858b3315bad34bde999efd724dd",
+            ),
+            (
+                blake2s_mac(&key[..32], b""),
+                "48a8997da407876b3d79c0d92325ad3b89cbb754d86ab71aee047ad345fd2c49",
+            ),
+        ];
+        for (received, expected) in tests {
+            assert_eq!(to_hex(received), expected);
+        }
+    }
+}
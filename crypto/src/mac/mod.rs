@@ -1,7 +1,11 @@
+mod blake2_mac;
 mod hmac;
 mod md4_mac;
 mod sha1_mac;
+mod verify;
 
+pub use blake2_mac::*;
 pub use hmac::*;
 pub use md4_mac::*;
 pub use sha1_mac::*;
+pub use verify::*;
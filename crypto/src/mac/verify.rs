@@ -0,0 +1,112 @@
+use crate::mac::{blake2b_mac, blake2s_mac, hmac_sha1, md4_mac, sha1_mac};
+use std::hint::black_box;
+
+/// Compares two byte strings in time depending only on their length, unlike `==` which stops at
+/// the first difference and leaks how many leading bytes are right (see set_4 ex_31).
+/// The length isn't secret: different lengths return false straight away.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let difference = a
+        .iter()
+        .zip(b)
+        .fold(0u8, |acc, (x, y)| black_box(acc | (x ^ y)));
+    difference == 0
+}
+
+/// A message authentication code. `MacExt` adds `verify` to every implementation.
+pub trait Mac {
+    fn compute(&self, key: &[u8], payload: &[u8]) -> Vec<u8>;
+}
+
+mod private {
+    pub trait Sealed {}
+    impl<T: super::Mac + ?Sized> Sealed for T {}
+}
+
+/// Sealed and implemented for every `Mac`: an implementation can't replace the comparison with
+/// a leaky one.
+pub trait MacExt: Mac + private::Sealed {
+    /// Checks `tag` against the MAC of `payload`, in constant time.
+    fn verify(&self, key: &[u8], payload: &[u8], tag: &[u8]) -> bool {
+        constant_time_eq(&self.compute(key, payload), tag)
+    }
+}
+
+impl<T: Mac + ?Sized> MacExt for T {}
+
+/// `hmac_sha1`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HmacSha1;
+impl Mac for HmacSha1 {
+    fn compute(&self, key: &[u8], payload: &[u8]) -> Vec<u8> {
+        hmac_sha1(key, payload)
+    }
+}
+
+/// `sha1_mac`, vulnerable to length extension.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha1Mac;
+impl Mac for Sha1Mac {
+    fn compute(&self, key: &[u8], payload: &[u8]) -> Vec<u8> {
+        sha1_mac(key, payload)
+    }
+}
+
+/// `md4_mac`, vulnerable to length extension.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Md4Mac;
+impl Mac for Md4Mac {
+    fn compute(&self, key: &[u8], payload: &[u8]) -> Vec<u8> {
+        md4_mac(key, payload)
+    }
+}
+
+/// `blake2b_mac`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Blake2bMac;
+impl Mac for Blake2bMac {
+    fn compute(&self, key: &[u8], payload: &[u8]) -> Vec<u8> {
+        blake2b_mac(key, payload)
+    }
+}
+
+/// `blake2s_mac`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Blake2sMac;
+impl Mac for Blake2sMac {
+    fn compute(&self, key: &[u8], payload: &[u8]) -> Vec<u8> {
+        blake2s_mac(key, payload)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mac::{constant_time_eq, hmac_sha1, HmacSha1, Mac, MacExt, Md4Mac, Sha1Mac};
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"", b""));
+        assert!(constant_time_eq(b"YELLOW SUBMARINE", b"YELLOW SUBMARINE"));
+        assert!(!constant_time_eq(b"YELLOW SUBMARINE", b"YELLOW SUBMARINF"));
+        assert!(!constant_time_eq(b"YELLOW SUBMARINE", b"ZELLOW SUBMARINE"));
+        assert!(!constant_time_eq(b"YELLOW", b"YELLOW SUBMARINE"));
+    }
+
+    #[test]
+    fn test_verify() {
+        let macs: [&dyn Mac; 3] = [&HmacSha1, &Sha1Mac, &Md4Mac];
+        for mac in macs {
+            let tag = mac.compute(b"key", b"message");
+            assert!(mac.verify(b"key", b"message", &tag));
+            assert!(!mac.verify(b"key", b"massage", &tag));
+            assert!(!mac.verify(b"kay", b"message", &tag));
+            assert!(!mac.verify(b"key", b"message", &tag[1..]));
+        }
+        assert_eq!(
+            HmacSha1.compute(b"key", b"message"),
+            hmac_sha1(b"key", b"message")
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use crate::aes;
+use crate::random::drbg::{add_assign_be, DrbgError, MAX_BYTES_PER_REQUEST, RESEED_INTERVAL};
+use crate::random::Drbg;
+
+const BLOCK_SIZE: usize = 16;
+const KEY_SIZE: usize = 16;
+/// seedlen: key and V.
+const SEED_SIZE: usize = KEY_SIZE + BLOCK_SIZE;
+
+/// CTR_DRBG (SP 800-90A 10.2.1) with AES-128 and the derivation function.
+pub struct CtrDrbg {
+    key: [u8; KEY_SIZE],
+    v: [u8; BLOCK_SIZE],
+    reseed_counter: u64,
+}
+
+/// BCC: the CBC-MAC of `data` with a zero IV.
+fn bcc(key: &[u8; KEY_SIZE], data: &[u8]) -> [u8; BLOCK_SIZE] {
+    let mut chaining = [0u8; BLOCK_SIZE];
+    for block in data.chunks(BLOCK_SIZE) {
+        for (c, b) in chaining.iter_mut().zip(block) {
+            *c ^= b;
+        }
+        chaining = aes::encrypt(&chaining, key);
+    }
+    chaining
+}
+
+/// Block_Cipher_df: derives `SEED_SIZE` bytes from `input`.
+fn block_cipher_df(input: &[u8]) -> [u8; SEED_SIZE] {
+    let mut s = [
+        &(input.len() as u32).to_be_bytes()[..],
+        &(SEED_SIZE as u32).to_be_bytes(),
+        input,
+        &[0x80],
+    ]
+    .concat();
+    s.resize(s.len().div_ceil(BLOCK_SIZE) * BLOCK_SIZE, 0);
+
+    let mut key = [0u8; KEY_SIZE];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    let mut temp = [0u8; SEED_SIZE];
+    for (i, chunk) in temp.chunks_mut(BLOCK_SIZE).enumerate() {
+        let mut iv = [0u8; BLOCK_SIZE];
+        iv[..4].copy_from_slice(&(i as u32).to_be_bytes());
+        chunk.copy_from_slice(&bcc(&key, &[&iv[..], &s].concat()));
+    }
+
+    key.copy_from_slice(&temp[..KEY_SIZE]);
+    let mut x = [0u8; BLOCK_SIZE];
+    x.copy_from_slice(&temp[KEY_SIZE..]);
+    let mut ret = [0u8; SEED_SIZE];
+    for chunk in ret.chunks_mut(BLOCK_SIZE) {
+        x = aes::encrypt(&x, &key);
+        chunk.copy_from_slice(&x);
+    }
+    ret
+}
+
+impl CtrDrbg {
+    /// The next block of the counter mode keystream.
+    fn next_block(&mut self) -> [u8; BLOCK_SIZE] {
+        add_assign_be(&mut self.v, &[0x01]);
+        aes::encrypt(&self.v, &self.key)
+    }
+
+    /// CTR_DRBG_Update
+    fn update(&mut self, provided_data: &[u8; SEED_SIZE]) {
+        let mut temp = [0u8; SEED_SIZE];
+        for chunk in temp.chunks_mut(BLOCK_SIZE) {
+            chunk.copy_from_slice(&self.next_block());
+        }
+        for (t, p) in temp.iter_mut().zip(provided_data) {
+            *t ^= p;
+        }
+        self.key.copy_from_slice(&temp[..KEY_SIZE]);
+        self.v.copy_from_slice(&temp[KEY_SIZE..]);
+    }
+}
+
+impl Drbg for CtrDrbg {
+    const SECURITY_STRENGTH: usize = 16;
+
+    fn instantiate(entropy: &[u8], nonce: &[u8], personalization: &[u8]) -> Self {
+        let mut drbg = Self {
+            key: [0; KEY_SIZE],
+            v: [0; BLOCK_SIZE],
+            reseed_counter: 1,
+        };
+        drbg.update(&block_cipher_df(
+            &[entropy, nonce, personalization].concat(),
+        ));
+        drbg
+    }
+
+    fn reseed(&mut self, entropy: &[u8], additional_input: &[u8]) {
+        self.update(&block_cipher_df(&[entropy, additional_input].concat()));
+        self.reseed_counter = 1;
+    }
+
+    fn generate(&mut self, output: &mut [u8], additional_input: &[u8]) -> Result<(), DrbgError> {
+        if self.reseed_counter > RESEED_INTERVAL {
+            return Err(DrbgError::ReseedRequired);
+        }
+        if output.len() > MAX_BYTES_PER_REQUEST {
+            return Err(DrbgError::RequestTooLarge);
+        }
+        let additional_input = if additional_input.is_empty() {
+            [0; SEED_SIZE]
+        } else {
+            let derived = block_cipher_df(additional_input);
+            self.update(&derived);
+            derived
+        };
+        for chunk in output.chunks_mut(BLOCK_SIZE) {
+            chunk.copy_from_slice(&self.next_block()[..chunk.len()]);
+        }
+        self.update(&additional_input);
+        self.reseed_counter += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::from_hex;
+    use crate::random::{CtrDrbg, Drbg};
+
+    // NIST CAVS 14.3 vectors for AES-128 with the derivation function, 512 bits returned.
+
+    fn hex(hex: &str) -> Vec<u8> {
+        from_hex(hex).unwrap()
+    }
+
+    #[test]
+    fn test_no_reseed() {
+        let mut drbg = CtrDrbg::instantiate(
+            &hex("890eb067acf7382eff80b0c73bc872c6"),
+            &hex("aad471ef3ef1d203"),
+            b"",
+        );
+        let mut output = [0u8; 64];
+        drbg.generate(&mut output, b"").unwrap();
+        drbg.generate(&mut output, b"").unwrap();
+        assert_eq!(
+            output.to_vec(),
+            hex(
+                "a5514ed7095f64f3d0d3a5760394ab42062f373a25072a6ea6bcfd8489e94af6cf18659fea22ed1c\
+                 a0a9e33f718b115ee536b12809c31b72b08ddd8be1910fa3"
+            )
+        );
+    }
+
+    #[test]
+    fn test_additional_input() {
+        let mut drbg = CtrDrbg::instantiate(
+            &hex("b408cefb5bc7157d3f26cb95a8b1d7ac"),
+            &hex("026c768fd577b92a"),
+            b"",
+        );
+        let mut output = [0u8; 64];
+        drbg.generate(&mut output, &hex("5737ef81dee365b6dadb3feebf5d1084"))
+            .unwrap();
+        drbg.generate(&mut output, &hex("3368a516b3431a3daaa60dc8743c8297"))
+            .unwrap();
+        assert_eq!(
+            output.to_vec(),
+            hex(
+                "4e909ebb24147a0004063a5e47ee044fead610d62324bd0f963f756fb91361e8b87e3a76a398143f\
+                 e88130fe1b547b661a6480c711b739f18a9df3ae51d41bc9"
+            )
+        );
+    }
+
+    #[test]
+    fn test_reseed() {
+        let entropy = hex("0f65da13dca407999d4773c2b4a11d851dea0a12c52bf64339dd291c80d8ca89");
+        let mut drbg = CtrDrbg::instantiate(&entropy[..16], &hex("5209e5b4ed82a234"), b"");
+        drbg.reseed(&entropy[16..], b"");
+        let mut output = [0u8; 64];
+        drbg.generate(&mut output, b"").unwrap();
+        drbg.generate(&mut output, b"").unwrap();
+        assert_eq!(
+            output.to_vec(),
+            hex(
+                "2859cc468a76b08661ffd23b28547ffd0997ad526a0f51261b99ed3a37bd407bf418dbe6c6c3e26e\
+                 d0ddefcb7474d899bd99f3655427519fc5b4057bcaf306d4"
+            )
+        );
+    }
+
+    #[test]
+    fn test_prediction_resistance() {
+        let entropy = hex(
+            "5d4041942bcf68864a4997d8171f1f9fef55a769b7eaf03fe082029bb32a2b9d8239e865c0a42e14b9\
+             64b9c09de85a20",
+        );
+        let mut drbg = CtrDrbg::instantiate(&entropy[..16], &hex("d4f1f4ae08bcb3e1"), b"");
+        let mut output = [0u8; 64];
+        for reseed in entropy[16..].chunks(16) {
+            drbg.reseed(reseed, b"");
+            drbg.generate(&mut output, b"").unwrap();
+        }
+        assert_eq!(
+            output.to_vec(),
+            hex(
+                "4155320287eedcf7d484c2c2a1e2eb64b9c9ce77c87202a1ae1616c7a5cfd1c687c7a0bfcc85bda4\
+                 8fdd4629fd330c22d0a76076f88fc7cd04037ee06b7af602"
+            )
+        );
+    }
+}
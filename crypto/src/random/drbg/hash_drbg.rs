@@ -0,0 +1,150 @@
+use crate::hash::sha1::sha1;
+use crate::random::drbg::{add_assign_be, DrbgError, MAX_BYTES_PER_REQUEST, RESEED_INTERVAL};
+use crate::random::Drbg;
+
+const OUTPUT_SIZE: usize = 20;
+/// seedlen for SHA-1: 440 bits.
+const SEED_SIZE: usize = 55;
+
+/// Hash_DRBG (SP 800-90A 10.1.1) with SHA-1.
+pub struct HashDrbg {
+    v: Vec<u8>,
+    c: Vec<u8>,
+    reseed_counter: u64,
+}
+
+/// Hash_df: derives `SEED_SIZE` bytes from `input`.
+fn hash_df(input: &[u8]) -> Vec<u8> {
+    let bits = (SEED_SIZE as u32 * 8).to_be_bytes();
+    let mut ret: Vec<u8> = (1..=SEED_SIZE.div_ceil(OUTPUT_SIZE) as u8)
+        .flat_map(|counter| sha1(&[&[counter][..], &bits, input].concat()))
+        .collect();
+    ret.truncate(SEED_SIZE);
+    ret
+}
+
+impl HashDrbg {
+    /// Derives V and C from the seed material.
+    fn seed(&mut self, seed_material: &[u8]) {
+        self.v = hash_df(seed_material);
+        self.c = hash_df(&[&[0x00][..], &self.v].concat());
+        self.reseed_counter = 1;
+    }
+
+    /// Hashgen: hashes of V, V + 1, V + 2, ...
+    fn hashgen(&self, output: &mut [u8]) {
+        let mut data = self.v.clone();
+        for chunk in output.chunks_mut(OUTPUT_SIZE) {
+            chunk.copy_from_slice(&sha1(&data)[..chunk.len()]);
+            add_assign_be(&mut data, &[0x01]);
+        }
+    }
+}
+
+impl Drbg for HashDrbg {
+    const SECURITY_STRENGTH: usize = 16;
+
+    fn instantiate(entropy: &[u8], nonce: &[u8], personalization: &[u8]) -> Self {
+        let mut drbg = Self {
+            v: vec![],
+            c: vec![],
+            reseed_counter: 1,
+        };
+        drbg.seed(&[entropy, nonce, personalization].concat());
+        drbg
+    }
+
+    fn reseed(&mut self, entropy: &[u8], additional_input: &[u8]) {
+        self.seed(&[&[0x01][..], &self.v, entropy, additional_input].concat());
+    }
+
+    fn generate(&mut self, output: &mut [u8], additional_input: &[u8]) -> Result<(), DrbgError> {
+        if self.reseed_counter > RESEED_INTERVAL {
+            return Err(DrbgError::ReseedRequired);
+        }
+        if output.len() > MAX_BYTES_PER_REQUEST {
+            return Err(DrbgError::RequestTooLarge);
+        }
+        if !additional_input.is_empty() {
+            let w = sha1(&[&[0x02][..], &self.v, additional_input].concat());
+            add_assign_be(&mut self.v, &w);
+        }
+        self.hashgen(output);
+        let h = sha1(&[&[0x03][..], &self.v].concat());
+        let c = self.c.clone();
+        add_assign_be(&mut self.v, &h);
+        add_assign_be(&mut self.v, &c);
+        add_assign_be(&mut self.v, &self.reseed_counter.to_be_bytes());
+        self.reseed_counter += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::from_hex;
+    use crate::random::{Drbg, HashDrbg};
+
+    // NIST CAVS 14.3 Hash_DRBG.rsp (no reseed), SHA-1, 640 bits returned.
+
+    #[test]
+    fn test_known_answer() {
+        // [PersonalizationStringLen = 0] [AdditionalInputLen = 0] COUNT = 0
+        let entropy = from_hex("136cf1c174e5a09f66b962d994396525").unwrap();
+        let nonce = from_hex("fff1c6645f19231f").unwrap();
+        let mut drbg = HashDrbg::instantiate(&entropy, &nonce, b"");
+        let mut output = [0u8; 80];
+        drbg.generate(&mut output, b"").unwrap();
+        drbg.generate(&mut output, b"").unwrap();
+        assert_eq!(
+            output.to_vec(),
+            from_hex(
+                "0e28130fa5ca11edd3293ca26fdb8ae1810611f78715082ed3841e7486f16677b28e33ffe0b9\
+                 3d98ba57ba358c1343ab2a26b4eb7940f5bc639384641ee80a25140331076268bd1ce702ad53\
+                 4dda0ed8"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_additional_input() {
+        // Not a CAVS entry: the answer of OpenSSL's HASH-DRBG for the same steps.
+        let entropy = from_hex("67671a2f53dd910a8b35840edb6a0a1e").unwrap();
+        let nonce = from_hex("78377b525757b494").unwrap();
+        let personalization = from_hex("ebe71c53e9a0a1e6334f2b873867c105").unwrap();
+        let mut drbg = HashDrbg::instantiate(&entropy, &nonce, &personalization);
+        let mut output = [0u8; 80];
+        drbg.generate(
+            &mut output,
+            &from_hex("ae8800d484a16747ba741efdfceb6966").unwrap(),
+        )
+        .unwrap();
+        drbg.generate(
+            &mut output,
+            &from_hex("be2ef27ca27798ad2a2e4aef9895c460").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            output.to_vec(),
+            from_hex(
+                "e9cee438e2b8505c18195c139ddfdb5d01e4b44efc1f7218e590b3f5e5407adb573babf68a50\
+                 313e1cf69b00d6faa5f37cd3d8228b5e5fa7c6f3821f558f7c2a8ea7e92e5163e829e2f41429\
+                 f2ff1c94"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reseed() {
+        let mut a = HashDrbg::instantiate(&[1; 16], &[2; 8], b"");
+        let mut b = HashDrbg::instantiate(&[1; 16], &[2; 8], b"");
+        b.reseed(&[3; 16], b"");
+        let (mut output_a, mut output_b) = ([0u8; 32], [0u8; 32]);
+        a.generate(&mut output_a, b"").unwrap();
+        b.generate(&mut output_b, b"").unwrap();
+        assert_ne!(output_a, output_b);
+        assert_eq!(b.reseed_counter, 2);
+    }
+}
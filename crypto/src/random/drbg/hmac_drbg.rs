@@ -0,0 +1,155 @@
+use crate::mac::hmac_sha1;
+use crate::random::drbg::{DrbgError, MAX_BYTES_PER_REQUEST, RESEED_INTERVAL};
+use crate::random::Drbg;
+
+const OUTPUT_SIZE: usize = 20;
+
+/// HMAC_DRBG (SP 800-90A 10.1.2) with HMAC-SHA1.
+pub struct HmacDrbg {
+    key: Vec<u8>,
+    v: Vec<u8>,
+    reseed_counter: u64,
+}
+
+impl HmacDrbg {
+    /// HMAC_DRBG_Update
+    fn update(&mut self, provided_data: &[u8]) {
+        for round in [0x00, 0x01] {
+            self.key = hmac_sha1(&self.key, &[&self.v, &[round][..], provided_data].concat());
+            self.v = hmac_sha1(&self.key, &self.v);
+            if provided_data.is_empty() {
+                break;
+            }
+        }
+    }
+}
+
+impl Drbg for HmacDrbg {
+    const SECURITY_STRENGTH: usize = 16;
+
+    fn instantiate(entropy: &[u8], nonce: &[u8], personalization: &[u8]) -> Self {
+        let mut drbg = Self {
+            key: vec![0x00; OUTPUT_SIZE],
+            v: vec![0x01; OUTPUT_SIZE],
+            reseed_counter: 1,
+        };
+        drbg.update(&[entropy, nonce, personalization].concat());
+        drbg
+    }
+
+    fn reseed(&mut self, entropy: &[u8], additional_input: &[u8]) {
+        self.update(&[entropy, additional_input].concat());
+        self.reseed_counter = 1;
+    }
+
+    fn generate(&mut self, output: &mut [u8], additional_input: &[u8]) -> Result<(), DrbgError> {
+        if self.reseed_counter > RESEED_INTERVAL {
+            return Err(DrbgError::ReseedRequired);
+        }
+        if output.len() > MAX_BYTES_PER_REQUEST {
+            return Err(DrbgError::RequestTooLarge);
+        }
+        if !additional_input.is_empty() {
+            self.update(additional_input);
+        }
+        for chunk in output.chunks_mut(OUTPUT_SIZE) {
+            self.v = hmac_sha1(&self.key, &self.v);
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
+        }
+        self.update(additional_input);
+        self.reseed_counter += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::from_hex;
+    use crate::random::{Drbg, HmacDrbg};
+
+    // NIST CAVS 14.3 vectors for SHA-1, 640 bits returned.
+
+    #[test]
+    fn test_no_reseed() {
+        let entropy = from_hex("e91b63309e93d1d08e30e8d556906875f59747c468b0d0da").unwrap();
+        let mut drbg = HmacDrbg::instantiate(&entropy[..16], &entropy[16..], b"");
+        let mut output = [0u8; 80];
+        drbg.generate(&mut output, b"").unwrap();
+        drbg.generate(&mut output, b"").unwrap();
+        assert_eq!(
+            output.to_vec(),
+            from_hex(
+                "b7928f9503a417110788f9d0c2585f8aee6fb73b220a626b3ab9825b7a9facc79723d7e1ba92\
+                 55e40e65c249b6082a7bc5e3f129d3d8f69b04ed1183419d6c4f2a13b304d2c5743f41c8b0ee\
+                 73225347"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reseed() {
+        let entropy = from_hex(
+            "03e7b41c95818eb0b667bfa8a175a82466a1e417a9b6b92fd17e98c2e50ee0db00d25c3364451e95",
+        )
+        .unwrap();
+        let personalization = from_hex("126dded5eb0bc81be37c10bcd9d5f793").unwrap();
+        let mut drbg = HmacDrbg::instantiate(&entropy[..16], &entropy[16..24], &personalization);
+        drbg.reseed(
+            &entropy[24..],
+            &from_hex("dc596d188e2343802240bc7f5cc60516").unwrap(),
+        );
+        let mut output = [0u8; 80];
+        drbg.generate(
+            &mut output,
+            &from_hex("14c8ec10f5bdde6b9e75898d7f9f03d0").unwrap(),
+        )
+        .unwrap();
+        drbg.generate(
+            &mut output,
+            &from_hex("31aa842afcc1daa94098241a87d6ddfc").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            output.to_vec(),
+            from_hex(
+                "4739b1bcf87404a2290829bd7a61f0b391a794c71c055c7cc513b28dcb5fdc88645bc9cb490f\
+                 41fab134c6b33ce9336571762754343961de671b02a47960b4b4e23c5bfb87dcc19b260b3bcb\
+                 921ae325"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_prediction_resistance() {
+        // With prediction resistance, each request reseeds with its additional input first.
+        let entropy = from_hex(
+            "680face90d7bca21d4a0edb7799ee5d8b7be9eeddd0e3b4b7cafe231630aa95a742c4e5f5f22c6a4\
+             1c0d7792898827948a589f822d1af7a6",
+        )
+        .unwrap();
+        let personalization = from_hex("f58c40ae70f7a55648a931a0a9313dd7").unwrap();
+        let mut drbg = HmacDrbg::instantiate(&entropy[..16], &entropy[16..24], &personalization);
+        let mut output = [0u8; 80];
+        drbg.reseed(
+            &entropy[24..40],
+            &from_hex("dc3663f062789cd15cbb20c3c18cd9d7").unwrap(),
+        );
+        drbg.generate(&mut output, b"").unwrap();
+        drbg.reseed(
+            &entropy[40..],
+            &from_hex("fe85b0ab14c696e69c24e7b5a137120c").unwrap(),
+        );
+        drbg.generate(&mut output, b"").unwrap();
+        assert_eq!(
+            output.to_vec(),
+            from_hex(
+                "68004b3a28f7f01cf9e9b5712079ef80871b08b9a91bcd2b9f094da48480b34cafd5596b0c0a\
+                 48e148dabc6f77b8ffaf187028e104137a4feb1c72b0c44fe8b1afaba5bcfd8667f2f55b4606\
+                 632e3cbc"
+            )
+            .unwrap()
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! The deterministic random bit generators of NIST SP 800-90A Rev. 1: HMAC_DRBG and Hash_DRBG
+//! over SHA-1, CTR_DRBG over AES-128 with the derivation function.
+//!
+//! Unlike MT19937, their outputs don't reveal the internal state, and the state is updated after
+//! each request so a later compromise doesn't reveal the previous outputs (backtracking
+//! resistance). They need real entropy though: `from_os_entropy` takes it from the OS, the
+//! explicit `instantiate` is for tests and known answer vectors.
+//!
+//! https://csrc.nist.gov/publications/detail/sp/800-90a/rev-1/final
+
+use crate::random::Rng;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use std::fmt::{Display, Formatter};
+
+mod ctr_drbg;
+mod hash_drbg;
+mod hmac_drbg;
+
+pub use ctr_drbg::*;
+pub use hash_drbg::*;
+pub use hmac_drbg::*;
+
+/// Number of requests allowed between two reseeds (2^48 for all three mechanisms).
+pub const RESEED_INTERVAL: u64 = 1 << 48;
+/// Largest output of a single request: 2^19 bits.
+pub const MAX_BYTES_PER_REQUEST: usize = 1 << 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrbgError {
+    /// `RESEED_INTERVAL` requests were made since the last (re)seed.
+    ReseedRequired,
+    /// More than `MAX_BYTES_PER_REQUEST` bytes were requested at once.
+    RequestTooLarge,
+}
+
+impl Display for DrbgError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DrbgError::ReseedRequired => write!(f, "Reseed required"),
+            DrbgError::RequestTooLarge => write!(f, "Request too large"),
+        }
+    }
+}
+
+pub trait Drbg {
+    /// Bytes of entropy needed for the security strength of the mechanism.
+    const SECURITY_STRENGTH: usize;
+
+    /// Instantiate function. The nonce should be at least half the entropy length; the
+    /// personalization string is optional and may be empty.
+    fn instantiate(entropy: &[u8], nonce: &[u8], personalization: &[u8]) -> Self
+    where
+        Self: Sized;
+
+    /// Reseed function: mixes fresh entropy into the state and resets the reseed counter.
+    fn reseed(&mut self, entropy: &[u8], additional_input: &[u8]);
+
+    /// Generate function: fills `output`, then updates the state.
+    fn generate(&mut self, output: &mut [u8], additional_input: &[u8]) -> Result<(), DrbgError>;
+
+    /// Instantiates with entropy and nonce from the OS.
+    fn from_os_entropy(personalization: &[u8]) -> Self
+    where
+        Self: Sized,
+    {
+        let mut entropy = vec![0u8; Self::SECURITY_STRENGTH];
+        let mut nonce = vec![0u8; Self::SECURITY_STRENGTH / 2];
+        OsRng.fill_bytes(&mut entropy);
+        OsRng.fill_bytes(&mut nonce);
+        Self::instantiate(&entropy, &nonce, personalization)
+    }
+
+    /// Reseeds with entropy from the OS.
+    fn reseed_from_os_entropy(&mut self, additional_input: &[u8]) {
+        let mut entropy = vec![0u8; Self::SECURITY_STRENGTH];
+        OsRng.fill_bytes(&mut entropy);
+        self.reseed(&entropy, additional_input);
+    }
+}
+
+/// Panics when a reseed is required, after 2^48 requests.
+impl<T: Drbg> Rng for T {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(MAX_BYTES_PER_REQUEST) {
+            self.generate(chunk, &[]).expect("DRBG failure");
+        }
+    }
+}
+
+/// (a + b) mod 2^(8 * a.len()), with both numbers in big endian and b no longer than a.
+fn add_assign_be(a: &mut [u8], b: &[u8]) {
+    let mut carry = 0u16;
+    let mut b = b.iter().rev();
+    for byte in a.iter_mut().rev() {
+        let sum = *byte as u16 + *b.next().unwrap_or(&0) as u16 + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::drbg::add_assign_be;
+    use crate::random::{Drbg, HmacDrbg, Rng};
+
+    #[test]
+    fn test_add_assign_be() {
+        let mut a = [0x00, 0xff, 0xff];
+        add_assign_be(&mut a, &[0x01]);
+        assert_eq!(a, [0x01, 0x00, 0x00]);
+        let mut a = [0xff, 0xff];
+        add_assign_be(&mut a, &[0x00, 0x02]);
+        assert_eq!(a, [0x00, 0x01]);
+    }
+
+    #[test]
+    fn test_rng() {
+        let mut a = HmacDrbg::instantiate(&[1; 16], &[2; 8], b"");
+        let mut b = HmacDrbg::instantiate(&[1; 16], &[2; 8], b"");
+        let mut expected = [0u8; 8];
+        b.generate(&mut expected, b"").unwrap();
+        assert_eq!(a.next_u64().to_le_bytes(), expected);
+
+        let mut os = HmacDrbg::from_os_entropy(b"test");
+        assert_ne!(os.next_u64(), a.next_u64());
+        let mut big = vec![0u8; 3 * 65536 + 1];
+        os.fill_bytes(&mut big);
+        assert!(big[3 * 65536 - 16..].iter().any(|b| *b != 0));
+    }
+}
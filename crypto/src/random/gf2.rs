@@ -0,0 +1,158 @@
+//! Linear algebra over GF(2), for the generators whose outputs are linear functions of the
+//! state bits: each known output bit is an equation "xor of some state bits = value".
+
+/// A vector of bits, packed in u64 words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BitVector {
+    words: Vec<u64>,
+}
+
+impl BitVector {
+    pub(crate) fn zeros(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    pub(crate) fn unit(len: usize, index: usize) -> Self {
+        let mut ret = Self::zeros(len);
+        ret.flip(index);
+        ret
+    }
+
+    pub(crate) fn get(&self, index: usize) -> bool {
+        self.words[index / 64] >> (index % 64) & 1 == 1
+    }
+
+    pub(crate) fn flip(&mut self, index: usize) {
+        self.words[index / 64] ^= 1 << (index % 64);
+    }
+
+    pub(crate) fn xor_assign(&mut self, other: &Self) {
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a ^= b;
+        }
+    }
+
+    /// The parity of the bits set in both vectors.
+    pub(crate) fn dot(&self, other: &Self) -> bool {
+        let ones: u32 = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones())
+            .sum();
+        ones % 2 == 1
+    }
+
+    /// The index of the first set bit at or after `from`.
+    fn next_set(&self, from: usize) -> Option<usize> {
+        let mut word = from / 64;
+        if word >= self.words.len() {
+            return None;
+        }
+        let mut bits = self.words[word] & (u64::MAX << (from % 64));
+        loop {
+            if bits != 0 {
+                return Some(word * 64 + bits.trailing_zeros() as usize);
+            }
+            word += 1;
+            bits = *self.words.get(word)?;
+        }
+    }
+}
+
+/// A system of linear equations, kept in echelon form: each row's first set bit is its pivot,
+/// and no other row has this bit as pivot.
+#[derive(Debug, Clone)]
+pub(crate) struct LinearSystem {
+    variables: usize,
+    rows: Vec<(BitVector, bool)>,
+    /// The row of each pivot column.
+    pivots: Vec<Option<usize>>,
+}
+
+impl LinearSystem {
+    pub(crate) fn new(variables: usize) -> Self {
+        Self {
+            variables,
+            rows: vec![],
+            pivots: vec![None; variables],
+        }
+    }
+
+    pub(crate) fn rank(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Adds the equation `row . x = value`.
+    /// Returns false if it contradicts the previous ones.
+    pub(crate) fn add_equation(&mut self, mut row: BitVector, mut value: bool) -> bool {
+        let mut column = 0;
+        while let Some(set) = row.next_set(column) {
+            match self.pivots[set] {
+                Some(index) => {
+                    // The pivot row starts at `set`: the bits before it aren't touched.
+                    let (pivot_row, pivot_value) = &self.rows[index];
+                    row.xor_assign(pivot_row);
+                    value ^= pivot_value;
+                    column = set + 1;
+                }
+                None => {
+                    self.pivots[set] = Some(self.rows.len());
+                    self.rows.push((row, value));
+                    return true;
+                }
+            }
+        }
+        // The equation is a combination of the others.
+        !value
+    }
+
+    /// A solution of the system, with the free variables set to 0.
+    pub(crate) fn solve(&self) -> BitVector {
+        let mut solution = BitVector::zeros(self.variables);
+        // From the last pivot, each variable only depends on the ones after it.
+        for column in (0..self.variables).rev() {
+            if let Some(index) = self.pivots[column] {
+                let (row, value) = &self.rows[index];
+                if row.dot(&solution) != *value {
+                    solution.flip(column);
+                }
+            }
+        }
+        solution
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::gf2::{BitVector, LinearSystem};
+
+    fn vector(bits: &[usize]) -> BitVector {
+        let mut ret = BitVector::zeros(130);
+        for bit in bits {
+            ret.flip(*bit);
+        }
+        ret
+    }
+
+    #[test]
+    fn test_linear_system() {
+        // x0 ^ x1 = 1, x1 ^ x129 = 0, x129 = 1
+        let mut system = LinearSystem::new(130);
+        assert!(system.add_equation(vector(&[0, 1]), true));
+        assert!(system.add_equation(vector(&[1, 129]), false));
+        assert!(system.add_equation(vector(&[129]), true));
+        // Redundant, then contradictory.
+        assert!(system.add_equation(vector(&[0, 129]), true));
+        assert!(!system.add_equation(vector(&[0]), true));
+        assert_eq!(system.rank(), 3);
+
+        let solution = system.solve();
+        assert!(!solution.get(0));
+        assert!(solution.get(1));
+        assert!(solution.get(129));
+        assert!(!solution.get(64));
+    }
+}
@@ -0,0 +1,103 @@
+//! Lattice reduction for the small lattices of the truncated generator attacks: LLL on an integer
+//! basis, with the Gram–Schmidt orthogonalization in floating point, then Babai's nearest plane
+//! for the closest vector. Good enough for a few dozen dimensions and 64-bit entries; the callers
+//! verify what they get anyway.
+
+const DELTA: f64 = 0.99;
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn to_f64(v: &[i128]) -> Vec<f64> {
+    v.iter().map(|x| *x as f64).collect()
+}
+
+/// The Gram–Schmidt vectors b*_i, and mu[i][j] = <b_i, b*_j> / <b*_j, b*_j>.
+fn gram_schmidt(basis: &[Vec<i128>]) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+    let n = basis.len();
+    let mut orthogonal: Vec<Vec<f64>> = Vec::with_capacity(n);
+    let mut mu = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        let b = to_f64(&basis[i]);
+        let mut b_star = b.clone();
+        for j in 0..i {
+            mu[i][j] = dot(&b, &orthogonal[j]) / dot(&orthogonal[j], &orthogonal[j]);
+            for (x, y) in b_star.iter_mut().zip(&orthogonal[j]) {
+                *x -= mu[i][j] * y;
+            }
+        }
+        orthogonal.push(b_star);
+    }
+    (orthogonal, mu)
+}
+
+/// LLL-reduces the rows of `basis` in place.
+pub(crate) fn lll(basis: &mut [Vec<i128>]) {
+    let n = basis.len();
+    let (mut orthogonal, mut mu) = gram_schmidt(basis);
+    let mut k = 1;
+    while k < n {
+        // Size reduction of b_k; the b* don't change.
+        for j in (0..k).rev() {
+            let q = mu[k][j].round();
+            if q != 0.0 {
+                let (before, after) = basis.split_at_mut(k);
+                for (x, y) in after[0].iter_mut().zip(&before[j]) {
+                    *x -= q as i128 * y;
+                }
+                let (above, below) = mu.split_at_mut(k);
+                for (x, y) in below[0][..j].iter_mut().zip(&above[j][..j]) {
+                    *x -= q * y;
+                }
+                below[0][j] -= q;
+            }
+        }
+        let norm_k = dot(&orthogonal[k], &orthogonal[k]);
+        let norm_previous = dot(&orthogonal[k - 1], &orthogonal[k - 1]);
+        if norm_k >= (DELTA - mu[k][k - 1] * mu[k][k - 1]) * norm_previous {
+            k += 1;
+        } else {
+            basis.swap(k, k - 1);
+            (orthogonal, mu) = gram_schmidt(basis);
+            k = (k - 1).max(1);
+        }
+    }
+}
+
+/// A lattice vector close to `target`, with Babai's nearest plane on a reduced basis.
+pub(crate) fn closest_vector(basis: &[Vec<i128>], target: &[i128]) -> Vec<i128> {
+    let (orthogonal, _) = gram_schmidt(basis);
+    let mut remainder = target.to_vec();
+    for i in (0..basis.len()).rev() {
+        let c = (dot(&to_f64(&remainder), &orthogonal[i]) / dot(&orthogonal[i], &orthogonal[i]))
+            .round() as i128;
+        for (x, y) in remainder.iter_mut().zip(&basis[i]) {
+            *x -= c * y;
+        }
+    }
+    target.iter().zip(&remainder).map(|(t, r)| t - r).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::lattice::{closest_vector, lll};
+
+    #[test]
+    fn test_lll() {
+        let mut basis = vec![vec![1, 1, 1], vec![-1, 0, 2], vec![3, 5, 6]];
+        lll(&mut basis);
+        assert_eq!(basis[..2], [vec![0, 1, 0], vec![1, 0, 1]]);
+        // The third vector depends on delta, but it's as short as possible.
+        assert_eq!(basis[2].iter().map(|x| x * x).sum::<i128>(), 5);
+    }
+
+    #[test]
+    fn test_closest_vector() {
+        // The vectors (x, 1000 x mod 5003).
+        let mut basis = vec![vec![1, 1000], vec![0, 5003]];
+        lll(&mut basis);
+        // 7 * (1, 1000) - 1 * (0, 5003) = (7, 1997), disturbed a little.
+        assert_eq!(closest_vector(&basis, &[8, 1995]), vec![7, 1997]);
+    }
+}
@@ -0,0 +1,323 @@
+//! Linear congruential generators: x' = (a x + c) mod 2^k, outputting a window of the bits of x.
+//!
+//! With a full output the state is simply the output. With truncated outputs (the high bits),
+//! the unknown low bits are recovered with a lattice: the states satisfy x_i = a^i x_0 + c_i mod
+//! 2^k, so the vector of the x_i - c_i is in the lattice generated by (1, a, a^2, ...) and the
+//! 2^k e_i, and the outputs give a vector close to it. Babai's nearest plane on the LLL-reduced
+//! basis finds it when the outputs give enough bits in total.
+//!
+//! The bits above the output window never influence the outputs (the modulus is a power of two),
+//! so they can't be recovered, but they aren't needed to predict the outputs either.
+
+use crate::random::lattice::{closest_vector, lll};
+use crate::random::Rng;
+
+/// Outputs used in the lattice, the others are only checked.
+const MAX_LATTICE_DIMENSION: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LcgParameters {
+    pub multiplier: u64,
+    pub increment: u64,
+    /// The modulus is 2^modulus_bits.
+    pub modulus_bits: u32,
+    /// The output is bits `output_shift..output_shift + output_bits` of the state.
+    pub output_shift: u32,
+    pub output_bits: u32,
+}
+
+/// glibc's `random` with a TYPE_0 state (`initstate` with less than 32 bytes), and the `rand` of
+/// many other libcs. The default glibc `rand` is an additive feedback generator instead.
+pub const GLIBC_TYPE_0: LcgParameters = LcgParameters {
+    multiplier: 1103515245,
+    increment: 12345,
+    modulus_bits: 31,
+    output_shift: 0,
+    output_bits: 31,
+};
+
+/// `java.util.Random`, with `nextInt()` as output.
+pub const JAVA: LcgParameters = LcgParameters {
+    multiplier: 0x5DEECE66D,
+    increment: 0xB,
+    modulus_bits: 48,
+    output_shift: 16,
+    output_bits: 32,
+};
+
+/// The `rand` of the Microsoft C runtime: 15 bits per output.
+pub const MSVC: LcgParameters = LcgParameters {
+    multiplier: 214013,
+    increment: 2531011,
+    modulus_bits: 32,
+    output_shift: 16,
+    output_bits: 15,
+};
+
+impl LcgParameters {
+    /// The same generator, outputting only the top `bits` bits of the state, like Java's
+    /// `nextInt(2^bits)`.
+    pub fn top_bits(self, bits: u32) -> Self {
+        assert!(
+            bits <= self.modulus_bits,
+            "{} output bits for a {} bits state",
+            bits,
+            self.modulus_bits
+        );
+        let parameters = Self {
+            output_shift: self.modulus_bits - bits,
+            output_bits: bits,
+            ..self
+        };
+        parameters.check();
+        parameters
+    }
+
+    /// Panics unless 0 < output_bits <= 32 and output_shift + output_bits <= modulus_bits <= 63:
+    /// the state fits in a u64 and the products of the lattice in an i128.
+    fn check(&self) {
+        assert!(
+            (1..=32).contains(&self.output_bits),
+            "Outputs of 1 to 32 bits, not {}",
+            self.output_bits
+        );
+        assert!(
+            self.modulus_bits <= 63,
+            "A modulus of at most 2^63, not 2^{}",
+            self.modulus_bits
+        );
+        assert!(
+            self.output_shift + self.output_bits <= self.modulus_bits,
+            "Output bits {}..{} outside of a {} bits state",
+            self.output_shift,
+            self.output_shift + self.output_bits,
+            self.modulus_bits
+        );
+    }
+
+    fn mask(&self) -> u64 {
+        (1 << self.modulus_bits) - 1
+    }
+
+    fn step(&self, state: u64) -> u64 {
+        state
+            .wrapping_mul(self.multiplier)
+            .wrapping_add(self.increment)
+            & self.mask()
+    }
+
+    fn output(&self, state: u64) -> u32 {
+        (state >> self.output_shift & ((1 << self.output_bits) - 1)) as u32
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Lcg {
+    pub parameters: LcgParameters,
+    pub state: u64,
+}
+
+impl Lcg {
+    /// Panics on parameters outside of 0 < output_bits <= 32 and
+    /// output_shift + output_bits <= modulus_bits <= 63.
+    pub fn new(parameters: LcgParameters, state: u64) -> Self {
+        parameters.check();
+        Self {
+            parameters,
+            state: state & parameters.mask(),
+        }
+    }
+
+    /// `srandom(seed)` with a TYPE_0 state.
+    pub fn glibc(seed: u32) -> Self {
+        Self::new(GLIBC_TYPE_0, if seed == 0 { 1 } else { seed as u64 })
+    }
+
+    /// `new Random(seed)`
+    pub fn java(seed: u64) -> Self {
+        Self::new(JAVA, seed ^ JAVA.multiplier)
+    }
+
+    /// `srand(seed)`
+    pub fn msvc(seed: u32) -> Self {
+        Self::new(MSVC, seed as u64)
+    }
+
+    /// Steps the state and returns its output bits.
+    pub fn next_output(&mut self) -> u32 {
+        self.state = self.parameters.step(self.state);
+        self.parameters.output(self.state)
+    }
+
+    /// The generator that produced `output`, when outputs are the full state.
+    pub fn from_full_output(parameters: LcgParameters, output: u32) -> Option<Self> {
+        (parameters.output_shift == 0 && parameters.output_bits == parameters.modulus_bits)
+            .then(|| Self::new(parameters, output as u64))
+    }
+
+    /// Recovers the generator from consecutive truncated outputs. The returned generator
+    /// continues after the last one, and its state bits above the output window are 0.
+    ///
+    /// Needs somewhat more known bits than the state has, e.g. 3 `nextInt()` or 16
+    /// `nextInt(256)` for Java.
+    pub fn from_truncated_outputs(parameters: LcgParameters, outputs: &[u32]) -> Option<Self> {
+        parameters.check();
+        // Working modulo 2^(top of the window), the outputs are the high bits of the state.
+        let reduced = LcgParameters {
+            modulus_bits: parameters.output_shift + parameters.output_bits,
+            ..parameters
+        };
+        let modulus = 1i128 << reduced.modulus_bits;
+        let unknown = reduced.output_shift;
+        let n = outputs.len().min(MAX_LATTICE_DIMENSION);
+        if n == 0 {
+            return None;
+        }
+
+        // x_i = a^i x_0 + c_i, target_i = the known bits of x_i - c_i, centred in the unknown ones.
+        // Below 2^63, so the products fit in an i128.
+        let (a, c) = (
+            reduced.multiplier as i128 % modulus,
+            reduced.increment as i128 % modulus,
+        );
+        let mut powers = vec![1i128];
+        let mut offsets = vec![0i128];
+        for i in 1..n {
+            powers.push(powers[i - 1] * a % modulus);
+            offsets.push((offsets[i - 1] * a + c) % modulus);
+        }
+        let half = if unknown == 0 {
+            0
+        } else {
+            1i128 << (unknown - 1)
+        };
+        let target: Vec<i128> = (0..n)
+            .map(|i| ((outputs[i] as i128) << unknown) + half - offsets[i])
+            .collect();
+
+        let mut basis = vec![powers];
+        for i in 1..n {
+            let mut row = vec![0; n];
+            row[i] = modulus;
+            basis.push(row);
+        }
+        lll(&mut basis);
+        let closest = closest_vector(&basis, &target);
+
+        let mut state = closest[0].rem_euclid(modulus) as u64;
+        if reduced.output(state) != outputs[0] {
+            return None;
+        }
+        for output in &outputs[1..] {
+            state = reduced.step(state);
+            if reduced.output(state) != *output {
+                return None;
+            }
+        }
+        Some(Self::new(parameters, state))
+    }
+}
+
+/// Concatenates as many outputs as needed for 32 bits, the first one in the high bits.
+impl Rng for Lcg {
+    fn next_u32(&mut self) -> u32 {
+        let mut ret = 0u64;
+        let mut bits = 0;
+        while bits < 32 {
+            ret = ret << self.parameters.output_bits | self.next_output() as u64;
+            bits += self.parameters.output_bits;
+        }
+        (ret >> (bits - 32)) as u32
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::{Lcg, LcgParameters, Rng, GLIBC_TYPE_0, JAVA, MSVC};
+
+    #[test]
+    fn test_known_outputs() {
+        // new Random(42).nextInt()
+        assert_eq!(Lcg::java(42).next_output() as i32, -1170105035);
+        // srand(1); rand()
+        let mut msvc = Lcg::msvc(1);
+        let outputs: Vec<u32> = (0..4).map(|_| msvc.next_output()).collect();
+        assert_eq!(outputs, vec![41, 18467, 6334, 26500]);
+        assert_eq!(Lcg::glibc(1).next_output(), 1103527590);
+        // Three 15 bits outputs make a u32, the top 2 bits of 6334 are 0.
+        assert_eq!(Lcg::msvc(1).next_u32(), (41 << 17) | (18467 << 2));
+    }
+
+    #[test]
+    fn test_full_output() {
+        let mut rng = Lcg::glibc(0xdeadbeef);
+        let output = rng.next_output();
+        let mut clone = Lcg::from_full_output(GLIBC_TYPE_0, output).unwrap();
+        for _ in 0..100 {
+            assert_eq!(clone.next_output(), rng.next_output());
+        }
+        assert!(Lcg::from_full_output(JAVA, output).is_none());
+    }
+
+    fn check_truncated(mut rng: Lcg, observed: usize) {
+        let parameters = rng.parameters;
+        let outputs: Vec<u32> = (0..observed).map(|_| rng.next_output()).collect();
+        let mut clone = Lcg::from_truncated_outputs(parameters, &outputs).unwrap();
+        for _ in 0..100 {
+            assert_eq!(clone.next_output(), rng.next_output());
+        }
+    }
+
+    #[test]
+    fn test_truncated_outputs() {
+        for seed in 0..10 {
+            check_truncated(Lcg::java(seed), 3);
+            check_truncated(Lcg::msvc(seed as u32), 6);
+            // nextInt(256): 8 bits out of 48.
+            let rng = Lcg::java(seed);
+            check_truncated(Lcg::new(JAVA.top_bits(8), rng.state), 16);
+        }
+    }
+
+    #[test]
+    fn test_wrong_outputs() {
+        assert!(Lcg::from_truncated_outputs(MSVC, &[1, 2, 3, 4, 5, 6, 7, 8]).is_none());
+    }
+
+    #[test]
+    fn test_large_modulus() {
+        // Knuth's MMIX constants, truncated to 63 bits: the largest modulus.
+        let parameters = LcgParameters {
+            multiplier: 6364136223846793005,
+            increment: 1442695040888963407,
+            modulus_bits: 63,
+            output_shift: 31,
+            output_bits: 32,
+        };
+        for seed in 0..10 {
+            check_truncated(Lcg::new(parameters, seed * 0x0123_4567_89ab_cdef), 4);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "49 output bits for a 48 bits state")]
+    fn test_too_many_top_bits() {
+        JAVA.top_bits(49);
+    }
+
+    #[test]
+    #[should_panic(expected = "Outputs of 1 to 32 bits, not 0")]
+    fn test_no_output_bits() {
+        Lcg::new(JAVA.top_bits(0), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "A modulus of at most 2^63, not 2^64")]
+    fn test_modulus_too_large() {
+        let parameters = LcgParameters {
+            modulus_bits: 64,
+            ..JAVA
+        };
+        Lcg::new(parameters, 1);
+    }
+}
@@ -0,0 +1,401 @@
+mod drbg;
+mod gf2;
+mod lattice;
+mod lcg;
+mod mt19937_64;
+mod mt19937_cipher;
+mod mt19937_solver;
+mod seed_cracker;
+mod sp800_22;
+mod xorshift;
+
+pub use drbg::*;
+pub use lcg::*;
+pub use mt19937_64::*;
+pub use mt19937_cipher::*;
+pub use mt19937_solver::*;
+pub use seed_cracker::*;
+pub use sp800_22::*;
+pub use xorshift::*;
+
+pub trait Rng {
+    fn next_u32(&mut self) -> u32;
+
+    /// Two outputs, the first one in the low half (like `rand_core`).
+    fn next_u64(&mut self) -> u64 {
+        let low = self.next_u32() as u64;
+        let high = self.next_u32() as u64;
+        (high << 32) | low
+    }
+
+    /// Fills `dest` with outputs in little endian, dropping the unused bytes of the last one.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    /// return uniform ditribution in [0,1)
+    fn rand(&mut self) -> f32 {
+        (self.next_u32() as f64 / 4294967296f64) as f32
+    }
+}
+
+/// Implements `rand::RngCore` and `rand::SeedableRng` for one of our generators, so it can be
+/// used with the `rand` crate. The seed is the little endian bytes of the integer seed.
+macro_rules! impl_rand_core {
+    ($rng:ty, $seed:ty) => {
+        impl rand::RngCore for $rng {
+            fn next_u32(&mut self) -> u32 {
+                crate::random::Rng::next_u32(self)
+            }
+            fn next_u64(&mut self) -> u64 {
+                crate::random::Rng::next_u64(self)
+            }
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                crate::random::Rng::fill_bytes(self, dest)
+            }
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+                crate::random::Rng::fill_bytes(self, dest);
+                Ok(())
+            }
+        }
+
+        impl rand::SeedableRng for $rng {
+            type Seed = [u8; std::mem::size_of::<$seed>()];
+
+            fn from_seed(seed: Self::Seed) -> Self {
+                Self::new_seed(<$seed>::from_le_bytes(seed))
+            }
+        }
+    };
+}
+pub(crate) use impl_rand_core;
+
+/// From wikipedia:
+/// Is not cryptographically secure, unless the CryptMT variant (discussed below) is used. The reason is
+/// that observing a sufficient number of iterations (624 in the case of MT19937, since this is the size
+/// of the state vector from which future iterations are produced) allows one to predict all future
+/// iterations.
+#[derive(Clone)]
+pub struct Mt19937MersenneTwisterRng {
+    pub mt: [u32; N],
+    index: usize,
+    seed: u32,
+}
+mod consts_32 {
+    pub const N: usize = 624;
+    pub const W: u32 = 32;
+    pub const M: usize = 397;
+    pub const R: u32 = 31;
+    pub const A: u32 = 0x9908B0DF;
+    pub const U: u32 = 11;
+    pub const D: u64 = 0xFFFFFFFF;
+    pub const S: u32 = 7;
+    pub const B: u64 = 0x9D2C5680;
+    pub const T: u32 = 15;
+    pub const C: u64 = 0xEFC60000;
+    pub const L: u32 = 18;
+    pub const F: u64 = 1812433253;
+    pub const LOWER_MASK: u64 = (1 << R) - 1;
+    pub const UPPER_MASK: u64 = (!LOWER_MASK) & 0xFFFFFFFF;
+    pub const DEFAULT_SEED: u32 = 5489;
+}
+
+use consts_32::*;
+impl Mt19937MersenneTwisterRng {
+    pub fn new() -> Self {
+        Self::new_seed(DEFAULT_SEED)
+    }
+    pub fn new_from_state(mt: [u32; N]) -> Self {
+        Self {
+            mt,
+            index: N,
+            seed: DEFAULT_SEED,
+        }
+    }
+    pub fn clone_from_output(original: &mut Self) -> Self {
+        let mut state = [0u32; N];
+        for i in 0..N {
+            state[i] =
+                Mt1993MersenneTwisterRngClonerFromOutput::untamper(original.extract_number());
+        }
+        Self::new_from_state(state)
+    }
+
+    #[inline]
+    fn _u32(x: u64) -> u32 {
+        let mut ret = [0u8; 4];
+        ret.copy_from_slice(&x.to_le_bytes()[..4]);
+        u32::from_le_bytes(ret)
+    }
+    pub fn new_seed(seed: u32) -> Self {
+        let mt = [0; N];
+        let index = N;
+        let mut ret = Self { mt, index, seed };
+        ret.initialize_with_seed();
+        ret
+    }
+    fn initialize_with_seed(&mut self) {
+        self.mt[0] = self.seed;
+        for i in 1..N {
+            let a = (self.mt[i - 1] ^ (self.mt[i - 1] >> (W - 2))) as u64;
+            self.mt[i] = Self::_u32(F * a + i as u64);
+        }
+    }
+    pub fn extract_number(&mut self) -> u32 {
+        if self.index >= N {
+            self.twist()
+        }
+        let y = self.mt[self.index] as u64;
+        self.index += 1;
+        Self::_u32(Self::tamper(y))
+    }
+    pub fn tamper(y: u64) -> u64 {
+        let y = y ^ ((y >> U) & D);
+        let y = y ^ ((y << S) & B);
+        let y = y ^ ((y << T) & C);
+        let y = y ^ (y >> L);
+        y
+    }
+
+    fn twist(&mut self) {
+        for i in 0..N {
+            let x = Self::_u32(
+                (self.mt[i] as u64 & UPPER_MASK) + (self.mt[(i + 1) % N] as u64 & LOWER_MASK),
+            );
+            let mut xa = x >> 1;
+            if x % 2 != 0 {
+                xa = xa ^ A;
+            }
+            self.mt[i] = self.mt[(i + M) % N] ^ xa;
+            self.index = 0
+        }
+    }
+
+    /// Inverts `twist`: mt[i] was computed from the old mt[i], the old mt[i + 1] and mt[i + M],
+    /// so going from the last word to the first one, the words needed are either new ones not
+    /// untwisted yet or old ones already recovered.
+    /// The low 31 bits of the old mt[0] are lost by the twist, they're left as garbage.
+    pub fn untwist(&mut self) {
+        // The x of the twist for mt[i], from x >> 1 (^ A if x is odd): A has its top bit set.
+        let x_of = |mt: &[u32; N], i: usize| {
+            let xa = mt[i] ^ mt[(i + M) % N];
+            if xa & 0x80000000 != 0 {
+                ((xa ^ A) << 1) | 1
+            } else {
+                xa << 1
+            }
+        };
+        for i in (0..N).rev() {
+            // The top bit of the old mt[i] is in the x of mt[i], its low bits in the x of
+            // mt[i - 1].
+            let upper = x_of(&self.mt, i) & UPPER_MASK as u32;
+            let lower = x_of(&self.mt, (i + N - 1) % N) & LOWER_MASK as u32;
+            self.mt[i] = upper | lower;
+        }
+    }
+
+    /// Moves the generator back: the next `outputs` outputs will be the last `outputs` ones.
+    pub fn rewind(&mut self, outputs: usize) {
+        for _ in 0..outputs {
+            if self.index == 0 {
+                self.untwist();
+                self.index = N;
+            }
+            self.index -= 1;
+        }
+    }
+
+    /// The seed, if `mt` is the state `initialize_with_seed` builds: mt[1] is
+    /// F * (seed ^ (seed >> 30)) + 1, and F is odd so it's invertible modulo 2^32.
+    pub fn seed_from_state(mt: &[u32; N]) -> Option<u32> {
+        let f = F as u32;
+        // Newton's iteration for the inverse modulo 2^32: each step doubles the correct bits.
+        let mut f_inverse = f;
+        for _ in 0..5 {
+            f_inverse = f_inverse.wrapping_mul(2u32.wrapping_sub(f.wrapping_mul(f_inverse)));
+        }
+        let y = mt[1].wrapping_sub(1).wrapping_mul(f_inverse);
+        let seed = y ^ (y >> 30);
+
+        // The low bits of mt[0] may be lost to the twist, check the rest.
+        let expected = Self::new_seed(seed);
+        (expected.mt[1..] == mt[1..] && (expected.mt[0] ^ mt[0]) & UPPER_MASK as u32 == 0)
+            .then_some(seed)
+    }
+
+    /// Recovers the seed of a generator, e.g. cloned from its first outputs, by untwisting up to
+    /// `max_twists` times until the state is a freshly seeded one.
+    pub fn recover_seed(&self, max_twists: usize) -> Option<u32> {
+        let mut state = Self::new_from_state(self.mt);
+        for _ in 0..=max_twists {
+            if let Some(seed) = Self::seed_from_state(&state.mt) {
+                return Some(seed);
+            }
+            state.untwist();
+        }
+        None
+    }
+}
+
+impl Rng for Mt19937MersenneTwisterRng {
+    fn next_u32(&mut self) -> u32 {
+        self.extract_number()
+    }
+}
+
+impl_rand_core!(Mt19937MersenneTwisterRng, u32);
+
+struct Mt1993MersenneTwisterRngClonerFromOutput {}
+impl Mt1993MersenneTwisterRngClonerFromOutput {
+    pub fn untamper_shift_left(z: u32, shift: u32, mask_const: u32) -> u32 {
+        let convert_to_mask = |k| {
+            let mut retval = 0;
+            for _ in (1..=k).rev() {
+                retval = retval << 1;
+                retval = retval | 1;
+            }
+            retval
+        };
+        let block_mask = convert_to_mask(shift);
+        let mut ret = z;
+        for block_mask_shift in (0..(W - shift)).step_by(shift as usize) {
+            ret = ret ^ (((ret & (block_mask << block_mask_shift)) << shift) & mask_const);
+        }
+        ret
+    }
+
+    pub fn untamper_shift_right(z: u32, shift: u32, mask_const: u32) -> u32 {
+        let convert_to_mask = |k| {
+            let mut retval = 0;
+            for _ in (1..=k).rev() {
+                retval = retval >> 1;
+                retval = retval | 0x80000000;
+            }
+            retval
+        };
+        let block_mask = convert_to_mask(shift);
+        let mut ret = z;
+        for block_mask_shift in (0..(W - shift)).step_by(shift as usize) {
+            ret = ret ^ (((ret & (block_mask >> block_mask_shift)) >> shift) & mask_const);
+        }
+        ret
+    }
+    pub fn untamper(y: u32) -> u32 {
+        let y = Self::untamper_shift_right(y, L, 0xFFFFFFFF);
+        let y = Self::untamper_shift_left(y, T, C as u32);
+        let y = Self::untamper_shift_left(y, S, B as u32);
+        let y = Self::untamper_shift_right(y, U, D as u32);
+        y
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::{
+        Mt19937MersenneTwisterRng, Mt19937_64MersenneTwisterRng,
+        Mt1993MersenneTwisterRngClonerFromOutput, Rng,
+    };
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_untamper() {
+        let y = 101;
+        let ret = Mt19937MersenneTwisterRng::tamper(y) as u32;
+        assert_eq!(
+            y as u32,
+            Mt1993MersenneTwisterRngClonerFromOutput::untamper(ret)
+        );
+    }
+
+    #[test]
+    fn test_to_u32() {
+        assert_eq!(
+            Mt19937MersenneTwisterRng::_u32(9948446125718u64),
+            1301868182
+        );
+    }
+    #[test]
+    fn test_rng() {
+        let mut rng = Mt19937MersenneTwisterRng::new();
+        let received: Vec<u32> = (0..10).map(|_| rng.extract_number()).collect();
+        let expected = [
+            3499211612, 581869302, 3890346734, 3586334585, 545404204, 4161255391, 3922919429,
+            949333985, 2715962298, 1323567403,
+        ]
+        .to_vec();
+        assert_eq!(expected, received);
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut rng = Mt19937MersenneTwisterRng::new();
+        let mut received = Mt19937MersenneTwisterRng::clone_from_output(&mut rng);
+        assert_eq!(rng.mt, received.mt);
+        for _ in 0..10 {
+            assert_eq!(rng.extract_number(), received.extract_number());
+        }
+    }
+
+    #[test]
+    fn test_rng_trait() {
+        let mut rng = Mt19937MersenneTwisterRng::new();
+        assert_eq!(Rng::next_u64(&mut rng), (581869302 << 32) | 3499211612);
+        let mut bytes = [0u8; 6];
+        rng.fill_bytes(&mut bytes);
+        assert_eq!(bytes[..4], 3890346734u32.to_le_bytes());
+        assert_eq!(bytes[4..], 3586334585u32.to_le_bytes()[..2]);
+        let x = rng.rand();
+        assert!((0.0..1.0).contains(&x));
+    }
+
+    #[test]
+    fn test_rand_core() {
+        let mut rng = Mt19937MersenneTwisterRng::from_seed(5489u32.to_le_bytes());
+        let mut expected = Mt19937MersenneTwisterRng::new();
+        assert_eq!(rand::RngCore::next_u32(&mut rng), expected.extract_number());
+
+        // Usable with everything in the rand crate.
+        let dice: Vec<u8> = (0..100)
+            .map(|_| rand::Rng::gen_range(&mut rng, 1..=6))
+            .collect();
+        assert!(dice.iter().all(|d| (1..=6).contains(d)));
+
+        let mut rng = Mt19937_64MersenneTwisterRng::from_seed(5489u64.to_le_bytes());
+        assert_eq!(rand::RngCore::next_u64(&mut rng), 14514284786278117030);
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut rng = Mt19937MersenneTwisterRng::new_seed(1234);
+        let outputs: Vec<u32> = (0..2000).map(|_| rng.extract_number()).collect();
+        let mut cloned = Mt19937MersenneTwisterRng::clone_from_output(&mut rng);
+        // The clone is at output 2000 + 624, go back to output 10.
+        cloned.rewind(2000 + 624 - 10);
+        for expected in &outputs[10..] {
+            assert_eq!(cloned.extract_number(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_recover_seed() {
+        for seed in [0, 5489, 0xdeadbeef, u32::MAX] {
+            let mut rng = Mt19937MersenneTwisterRng::new_seed(seed);
+            assert_eq!(
+                Mt19937MersenneTwisterRng::seed_from_state(&rng.mt),
+                Some(seed)
+            );
+            let cloned = Mt19937MersenneTwisterRng::clone_from_output(&mut rng);
+            assert_eq!(cloned.recover_seed(1), Some(seed));
+        }
+        let mut rng = Mt19937MersenneTwisterRng::new_seed(42);
+        for _ in 0..624 * 3 {
+            rng.extract_number();
+        }
+        let cloned = Mt19937MersenneTwisterRng::clone_from_output(&mut rng);
+        assert_eq!(cloned.recover_seed(2), None);
+        assert_eq!(cloned.recover_seed(4), Some(42));
+    }
+}
@@ -0,0 +1,171 @@
+//! The 64 bits Mersenne Twister, the default of C++ `std::mt19937_64` and of many 64 bits
+//! runtimes. Same structure as the 32 bits one, with its own constants: 312 words of state, so
+//! 312 outputs are enough to clone it.
+
+use crate::random::{impl_rand_core, Rng};
+
+mod consts_64 {
+    pub const N: usize = 312;
+    pub const M: usize = 156;
+    pub const R: u32 = 31;
+    pub const A: u64 = 0xB5026F5AA96619E9;
+    pub const U: u32 = 29;
+    pub const D: u64 = 0x5555555555555555;
+    pub const S: u32 = 17;
+    pub const B: u64 = 0x71D67FFFEDA60000;
+    pub const T: u32 = 37;
+    pub const C: u64 = 0xFFF7EEE000000000;
+    pub const L: u32 = 43;
+    pub const F: u64 = 6364136223846793005;
+    pub const LOWER_MASK: u64 = (1 << R) - 1;
+    pub const UPPER_MASK: u64 = !LOWER_MASK;
+    pub const DEFAULT_SEED: u64 = 5489;
+}
+
+use consts_64::*;
+
+#[derive(Debug, Clone)]
+pub struct Mt19937_64MersenneTwisterRng {
+    pub mt: [u64; N],
+    index: usize,
+}
+
+impl Mt19937_64MersenneTwisterRng {
+    pub fn new() -> Self {
+        Self::new_seed(DEFAULT_SEED)
+    }
+
+    pub fn new_seed(seed: u64) -> Self {
+        let mut mt = [0; N];
+        mt[0] = seed;
+        for i in 1..N {
+            mt[i] = F
+                .wrapping_mul(mt[i - 1] ^ (mt[i - 1] >> 62))
+                .wrapping_add(i as u64);
+        }
+        Self { mt, index: N }
+    }
+
+    pub fn new_from_state(mt: [u64; N]) -> Self {
+        Self { mt, index: N }
+    }
+
+    /// Rebuilds the state from the next 312 outputs of `original`.
+    pub fn clone_from_output(original: &mut Self) -> Self {
+        let mut state = [0u64; N];
+        for word in state.iter_mut() {
+            *word = Self::untamper(original.extract_number());
+        }
+        Self::new_from_state(state)
+    }
+
+    pub fn extract_number(&mut self) -> u64 {
+        if self.index >= N {
+            self.twist()
+        }
+        let y = self.mt[self.index];
+        self.index += 1;
+        Self::tamper(y)
+    }
+
+    pub fn tamper(y: u64) -> u64 {
+        let y = y ^ ((y >> U) & D);
+        let y = y ^ ((y << S) & B);
+        let y = y ^ ((y << T) & C);
+        y ^ (y >> L)
+    }
+
+    pub fn untamper(y: u64) -> u64 {
+        let y = untamper_shift_right(y, L, u64::MAX);
+        let y = untamper_shift_left(y, T, C);
+        let y = untamper_shift_left(y, S, B);
+        untamper_shift_right(y, U, D)
+    }
+
+    fn twist(&mut self) {
+        for i in 0..N {
+            let x = (self.mt[i] & UPPER_MASK) | (self.mt[(i + 1) % N] & LOWER_MASK);
+            let mut xa = x >> 1;
+            if x & 1 == 1 {
+                xa ^= A;
+            }
+            self.mt[i] = self.mt[(i + M) % N] ^ xa;
+        }
+        self.index = 0;
+    }
+}
+
+impl Default for Mt19937_64MersenneTwisterRng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rng for Mt19937_64MersenneTwisterRng {
+    /// The high half of an output.
+    fn next_u32(&mut self) -> u32 {
+        (self.extract_number() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.extract_number()
+    }
+}
+
+impl_rand_core!(Mt19937_64MersenneTwisterRng, u64);
+
+/// Inverts y = x ^ ((x >> shift) & mask): each pass fixes `shift` more bits, from the top.
+fn untamper_shift_right(y: u64, shift: u32, mask: u64) -> u64 {
+    let mut x = y;
+    for _ in 0..64 / shift {
+        x = y ^ ((x >> shift) & mask);
+    }
+    x
+}
+
+/// Inverts y = x ^ ((x << shift) & mask): each pass fixes `shift` more bits, from the bottom.
+fn untamper_shift_left(y: u64, shift: u32, mask: u64) -> u64 {
+    let mut x = y;
+    for _ in 0..64 / shift {
+        x = y ^ ((x << shift) & mask);
+    }
+    x
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::Mt19937_64MersenneTwisterRng;
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Mt19937_64MersenneTwisterRng::new();
+        assert_eq!(rng.extract_number(), 14514284786278117030);
+        // The C++ standard requires the 10000th output of a default std::mt19937_64 to be this.
+        let mut rng = Mt19937_64MersenneTwisterRng::new();
+        let received = (0..10000).map(|_| rng.extract_number()).last();
+        assert_eq!(received, Some(9981545732273789042));
+    }
+
+    #[test]
+    fn test_untamper() {
+        for y in [0, 101, u64::MAX, 0x0123456789abcdef] {
+            let tampered = Mt19937_64MersenneTwisterRng::tamper(y);
+            assert_eq!(Mt19937_64MersenneTwisterRng::untamper(tampered), y);
+        }
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut rng = Mt19937_64MersenneTwisterRng::new_seed(0xdeadbeef);
+        rng.extract_number();
+        let mut cloned = Mt19937_64MersenneTwisterRng::clone_from_output(&mut rng);
+        for _ in 0..1000 {
+            assert_eq!(rng.extract_number(), cloned.extract_number());
+        }
+        // A fork goes on independently from the same position.
+        let mut fork = rng.clone();
+        for _ in 0..1000 {
+            assert_eq!(rng.extract_number(), fork.extract_number());
+        }
+    }
+}
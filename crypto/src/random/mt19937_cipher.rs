@@ -0,0 +1,266 @@
+//! The MT19937 stream cipher of cryptopals 24: the key seeds the generator and each output gives
+//! a byte of keystream, its low 8 bits. Not a secure cipher: 32-bit keys are brute forced, and
+//! a few thousand bytes of keystream give the whole state, see `Mt19937Solver`.
+//!
+//! Tokens made the same way, seeded from the current time, are what `Mt19937TokenDetector`
+//! looks for.
+
+use crate::random::{Mt19937MersenneTwisterRng, Mt19937SeedCracker, Mt19937Solver};
+use std::ops::RangeInclusive;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const TOKEN_SIZE: usize = 16;
+/// Keystream bytes to clone the generator. The low bytes are far from independent: the rank
+/// of the equations only reaches 19937 after about 3750 bytes, instead of 19937 / 8. The
+/// remaining equations make a stream from another generator inconsistent.
+pub const CLONE_MIN_BYTES: usize = 3800;
+
+/// A byte of keystream: the low 8 bits of the next output.
+pub fn mt19937_keystream_byte(rng: &mut Mt19937MersenneTwisterRng) -> u8 {
+    rng.extract_number() as u8
+}
+
+/// The keystream of `key`.
+pub struct Mt19937Keystream {
+    rng: Mt19937MersenneTwisterRng,
+}
+
+impl Mt19937Keystream {
+    pub fn new(key: u32) -> Self {
+        Self {
+            rng: Mt19937MersenneTwisterRng::new_seed(key),
+        }
+    }
+}
+
+impl Iterator for Mt19937Keystream {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(mt19937_keystream_byte(&mut self.rng))
+    }
+}
+
+pub fn mt19937_encrypt(plaintext: &[u8], key: u32) -> Vec<u8> {
+    plaintext
+        .iter()
+        .zip(Mt19937Keystream::new(key))
+        .map(|(a, b)| a ^ b)
+        .collect()
+}
+
+pub fn mt19937_decrypt(ciphertext: &[u8], key: u32) -> Vec<u8> {
+    mt19937_encrypt(ciphertext, key)
+}
+
+/// Brute forces the key of a ciphertext ending with `known_suffix`. 16-bit keys take a moment,
+/// the full 32-bit space takes minutes.
+pub fn mt19937_break(
+    ciphertext: &[u8],
+    known_suffix: &[u8],
+    keys: RangeInclusive<u32>,
+) -> Option<u32> {
+    let offset = ciphertext.len().checked_sub(known_suffix.len())?;
+    let keystream: Vec<u8> = ciphertext[offset..]
+        .iter()
+        .zip(known_suffix)
+        .map(|(a, b)| a ^ b)
+        .collect();
+    let key = Mt19937SeedCracker::new()
+        .keystream(offset, &keystream, mt19937_keystream_byte)
+        .find(keys);
+    key
+}
+
+fn now() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as u32
+}
+
+/// A password reset token: the keystream of the current timestamp.
+pub fn mt19937_password_reset_token() -> [u8; TOKEN_SIZE] {
+    mt19937_token_at(now())
+}
+
+/// The token generated at `timestamp`.
+pub fn mt19937_token_at(timestamp: u32) -> [u8; TOKEN_SIZE] {
+    let mut token = [0u8; TOKEN_SIZE];
+    for (byte, key) in token.iter_mut().zip(Mt19937Keystream::new(timestamp)) {
+        *byte = key;
+    }
+    token
+}
+
+/// How a token was generated.
+#[derive(Clone)]
+pub enum Mt19937TokenOrigin {
+    /// It starts the keystream of the generator seeded with this timestamp.
+    TimeSeeded(u32),
+    /// It's enough keystream of a single generator to clone it. The generator is positioned
+    /// after the token, so it predicts the next tokens.
+    ClonedFromOutput(Box<Mt19937MersenneTwisterRng>),
+}
+
+/// Flags MT19937 keystreams: a token seeded from a time close to now, or a stream of tokens long
+/// enough (`CLONE_MIN_BYTES`) to recover the state of the generator, whatever its seed.
+pub struct Mt19937TokenDetector {
+    now: u32,
+    window: u32,
+}
+
+impl Default for Mt19937TokenDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mt19937TokenDetector {
+    /// Looks for seeds within an hour of now.
+    pub fn new() -> Self {
+        Self {
+            now: now(),
+            window: 60 * 60,
+        }
+    }
+
+    /// The time the tokens were collected.
+    pub fn at(mut self, timestamp: u32) -> Self {
+        self.now = timestamp;
+        self
+    }
+
+    /// Seeds are searched within `seconds` of the collection time, both ways.
+    pub fn window(mut self, seconds: u32) -> Self {
+        self.window = seconds;
+        self
+    }
+
+    pub fn detect(&self, token: &[u8]) -> Option<Mt19937TokenOrigin> {
+        self.detect_time_seed(token)
+            .map(Mt19937TokenOrigin::TimeSeeded)
+            .or_else(|| {
+                self.detect_clone(token)
+                    .map(Mt19937TokenOrigin::ClonedFromOutput)
+            })
+    }
+
+    fn detect_time_seed(&self, token: &[u8]) -> Option<u32> {
+        if token.is_empty() {
+            return None;
+        }
+        // Comparing more than a few bytes is useless, and slow for long streams.
+        let prefix = &token[..token.len().min(TOKEN_SIZE)];
+        let seeds = self.now.saturating_sub(self.window)..=self.now.saturating_add(self.window);
+        Mt19937SeedCracker::new()
+            .keystream(0, prefix, mt19937_keystream_byte)
+            .find(seeds)
+            .filter(|seed| {
+                token.len() <= TOKEN_SIZE
+                    || Mt19937Keystream::new(*seed)
+                        .zip(token)
+                        .all(|(a, b)| a == *b)
+            })
+    }
+
+    fn detect_clone(&self, token: &[u8]) -> Option<Box<Mt19937MersenneTwisterRng>> {
+        if token.len() < CLONE_MIN_BYTES {
+            return None;
+        }
+        let mut solver = Mt19937Solver::new();
+        for (position, byte) in token.iter().enumerate() {
+            solver.add_bits(position, 0xFF, *byte as u32);
+        }
+        let mut solution = solver.solve()?;
+        if solution.rank < 19937 {
+            return None;
+        }
+        for _ in token {
+            solution.rng.extract_number();
+        }
+        Some(Box::new(solution.rng))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::{
+        mt19937_break, mt19937_decrypt, mt19937_encrypt, mt19937_keystream_byte, mt19937_token_at,
+        Mt19937Keystream, Mt19937MersenneTwisterRng, Mt19937TokenDetector, Mt19937TokenOrigin,
+        CLONE_MIN_BYTES,
+    };
+
+    #[test]
+    fn test_cipher() {
+        const PT: &[u8] = b"hello world";
+        let key = 0xdeadbeef;
+        let ciphertext = mt19937_encrypt(PT, key);
+        assert_ne!(ciphertext, PT);
+        assert_eq!(mt19937_decrypt(&ciphertext, key), PT);
+    }
+
+    #[test]
+    fn test_break() {
+        let key = 0x1234_5678;
+        let plaintext = [&b"random prefix"[..], b"AAAAAAAAAAAAAA"].concat();
+        let ciphertext = mt19937_encrypt(&plaintext, key);
+        assert_eq!(
+            mt19937_break(&ciphertext, b"AAAAAAAAAAAAAA", 0x1230_0000..=0x1240_0000),
+            Some(key)
+        );
+        assert_eq!(
+            mt19937_break(&ciphertext, b"BBBBBBBBBBBBBB", 0..=0xffff),
+            None
+        );
+    }
+
+    #[test]
+    fn test_detect_time_seeded() {
+        let now = 1_700_000_000;
+        let token = mt19937_token_at(now - 100);
+        let detector = Mt19937TokenDetector::new().at(now).window(600);
+        assert!(matches!(
+            detector.detect(&token),
+            Some(Mt19937TokenOrigin::TimeSeeded(seed)) if seed == now - 100
+        ));
+        // Outside of the window.
+        assert!(detector.detect(&mt19937_token_at(now - 1000)).is_none());
+        assert!(detector.detect(b"aaaaaaaaaaaaaaaa").is_none());
+    }
+
+    #[test]
+    fn test_detect_cloned() {
+        // A random seed, but 240 tokens in a row.
+        let mut rng = Mt19937MersenneTwisterRng::new_seed(0x5eed_1234);
+        let tokens: Vec<u8> = (0..240 * 16)
+            .map(|_| mt19937_keystream_byte(&mut rng))
+            .collect();
+        assert!(tokens.len() >= CLONE_MIN_BYTES);
+        let detector = Mt19937TokenDetector::new().at(0).window(10);
+        match detector.detect(&tokens) {
+            Some(Mt19937TokenOrigin::ClonedFromOutput(mut clone)) => {
+                for _ in 0..100 {
+                    assert_eq!(clone.extract_number(), rng.extract_number());
+                }
+            }
+            _ => panic!("Not cloned"),
+        }
+
+        // Not MT19937.
+        let other: Vec<u8> = (0..tokens.len()).map(|i| (i * i / 7) as u8).collect();
+        assert!(detector.detect(&other).is_none());
+        // Too short.
+        assert!(detector.detect(&tokens[..CLONE_MIN_BYTES - 1]).is_none());
+    }
+
+    #[test]
+    fn test_keystream() {
+        let mut rng = Mt19937MersenneTwisterRng::new_seed(7);
+        let expected: Vec<u8> = (0..8).map(|_| rng.extract_number() as u8).collect();
+        assert_eq!(
+            Mt19937Keystream::new(7).take(8).collect::<Vec<_>>(),
+            expected
+        );
+    }
+}
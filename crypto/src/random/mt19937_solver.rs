@@ -0,0 +1,226 @@
+//! MT19937 state recovery from partial outputs.
+//!
+//! `clone_from_output` needs 624 full consecutive outputs. But the twist and the tempering are
+//! both linear over GF(2): every output bit is a xor of state bits. So any known output bit, at
+//! any position, gives a linear equation on the 19968 state bits, and with enough independent
+//! equations Gaussian elimination recovers the state. That covers truncated outputs (floats,
+//! bytes, `x % 2^k`, `x >> k`), ranges like `(x / 2^32 * 100) as u8`, and gaps in the outputs.
+//!
+//! Only 19937 bits of the state matter (the low 31 bits of the first word are dropped by the
+//! twist), so at least 19937 independent bits are needed.
+//!
+//! The unknowns are the state producing the first observed block of 624 outputs. The outputs of
+//! that block only involve their own word, those of the next block three words, and so on:
+//! observations close to the start give sparse equations, which are much faster to eliminate.
+
+use crate::random::gf2::{BitVector, LinearSystem};
+use crate::random::Mt19937MersenneTwisterRng;
+
+const N: usize = 624;
+const M: usize = 397;
+const A: u32 = 0x9908B0DF;
+const STATE_BITS: usize = N * 32;
+
+/// A recovered generator, and how much the observations determined it.
+pub struct Mt19937Solution {
+    /// Its next output is the output at position 0.
+    pub rng: Mt19937MersenneTwisterRng,
+    /// Number of independent equations. Below 19937 some state bits were guessed, and the
+    /// predictions depending on them are likely wrong.
+    pub rank: usize,
+}
+
+/// Known bits of the outputs of an MT19937, counted from position 0.
+#[derive(Debug, Clone, Default)]
+pub struct Mt19937Solver {
+    /// (position, mask of the known bits, their values)
+    observations: Vec<(usize, u32, u32)>,
+}
+
+impl Mt19937Solver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The output at `position` is `value`.
+    pub fn add_output(&mut self, position: usize, value: u32) {
+        self.add_bits(position, u32::MAX, value);
+    }
+
+    /// The bits of the output at `position` selected by `mask` are the ones of `value`.
+    pub fn add_bits(&mut self, position: usize, mask: u32, value: u32) {
+        self.observations.push((position, mask, value & mask));
+    }
+
+    /// The output at `position` is in `low..=high`: the bits in common between all the values
+    /// of the range are known, e.g. the top bits for `(x as f64 / 2^32 * 100.0) as u8`.
+    pub fn add_range(&mut self, position: usize, low: u32, high: u32) {
+        let common = (low ^ high).leading_zeros();
+        let mask = u32::MAX.checked_shl(32 - common).unwrap_or(0);
+        self.add_bits(position, mask, low);
+    }
+
+    /// Recovers a state consistent with all the observations, or `None` if there's none (an
+    /// observation is wrong, or it's not MT19937).
+    pub fn solve(&self) -> Option<Mt19937Solution> {
+        let mut observations = self.observations.clone();
+        observations.sort_by_key(|(position, _, _)| *position);
+
+        let tempering = tempering_rows();
+        let mut state = SymbolicState::new();
+        let mut block = 0;
+        let mut system = LinearSystem::new(STATE_BITS);
+        for (position, mask, value) in observations {
+            while block < position / N {
+                state.twist();
+                block += 1;
+            }
+            let word = &state.words[position % N];
+            for bit in (0..32).filter(|bit| mask >> bit & 1 == 1) {
+                let mut row = BitVector::zeros(STATE_BITS);
+                for source in (0..32).filter(|source| tempering[bit] >> source & 1 == 1) {
+                    row.xor_assign(&word[source]);
+                }
+                if !system.add_equation(row, value >> bit & 1 == 1) {
+                    return None;
+                }
+            }
+        }
+
+        let solution = system.solve();
+        let mut mt = [0u32; N];
+        for (i, word) in mt.iter_mut().enumerate() {
+            for bit in 0..32 {
+                if solution.get(i * 32 + bit) {
+                    *word |= 1 << bit;
+                }
+            }
+        }
+        Some(Mt19937Solution {
+            rng: Mt19937MersenneTwisterRng {
+                mt,
+                index: 0,
+                seed: 0,
+            },
+            rank: system.rank(),
+        })
+    }
+}
+
+/// The tempering as a matrix: bit i of the output is the xor of the state bits set in row i.
+fn tempering_rows() -> [u32; 32] {
+    let mut rows = [0u32; 32];
+    for source in 0..32 {
+        let tempered = Mt19937MersenneTwisterRng::tamper(1 << source) as u32;
+        for (bit, row) in rows.iter_mut().enumerate() {
+            if tempered >> bit & 1 == 1 {
+                *row |= 1 << source;
+            }
+        }
+    }
+    rows
+}
+
+/// The MT19937 state where each bit is a linear combination of the unknown bits.
+struct SymbolicState {
+    words: Vec<[BitVector; 32]>,
+}
+
+impl SymbolicState {
+    fn new() -> Self {
+        let words = (0..N)
+            .map(|i| std::array::from_fn(|bit| BitVector::unit(STATE_BITS, i * 32 + bit)))
+            .collect();
+        Self { words }
+    }
+
+    /// The twist of `Mt19937MersenneTwisterRng`, on symbolic bits.
+    fn twist(&mut self) {
+        for i in 0..N {
+            // x = upper bit of mt[i] | lower 31 bits of mt[i + 1]
+            let x: [BitVector; 32] = std::array::from_fn(|bit| {
+                if bit == 31 {
+                    self.words[i][31].clone()
+                } else {
+                    self.words[(i + 1) % N][bit].clone()
+                }
+            });
+            // mt[i] = mt[i + M] ^ (x >> 1) ^ (A if x is odd)
+            let mut next = self.words[(i + M) % N].clone();
+            for bit in 0..31 {
+                next[bit].xor_assign(&x[bit + 1]);
+            }
+            for (bit, next_bit) in next.iter_mut().enumerate() {
+                if A >> bit & 1 == 1 {
+                    next_bit.xor_assign(&x[0]);
+                }
+            }
+            self.words[i] = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::{Mt19937MersenneTwisterRng, Mt19937Solver};
+
+    #[test]
+    fn test_truncated_outputs() {
+        let mut rng = Mt19937MersenneTwisterRng::new_seed(0x1234);
+        // Only the top 16 bits of each output are known.
+        let mut solver = Mt19937Solver::new();
+        for position in 0..3 * 624 {
+            solver.add_bits(position, 0xFFFF0000, rng.extract_number());
+        }
+        let mut solution = solver.solve().unwrap();
+        assert!(solution.rank >= 19937);
+        let mut original = Mt19937MersenneTwisterRng::new_seed(0x1234);
+        for _ in 0..3 * 624 {
+            assert_eq!(
+                solution.rng.extract_number() >> 16,
+                original.extract_number() >> 16
+            );
+        }
+        for _ in 0..1000 {
+            assert_eq!(solution.rng.extract_number(), rng.extract_number());
+        }
+    }
+
+    #[test]
+    fn test_outputs_with_gaps() {
+        let mut rng = Mt19937MersenneTwisterRng::new_seed(42);
+        let mut solver = Mt19937Solver::new();
+        for position in 0..3 * 624 {
+            let output = rng.extract_number();
+            // One output out of three is missing, one only gives its low byte.
+            match position % 3 {
+                0 => {}
+                1 => solver.add_bits(position, 0xFF, output),
+                _ => solver.add_output(position, output),
+            }
+        }
+        let mut solution = solver.solve().unwrap();
+        assert!(solution.rank >= 19937);
+        for _ in 0..3 * 624 {
+            solution.rng.extract_number();
+        }
+        for _ in 0..1000 {
+            assert_eq!(solution.rng.extract_number(), rng.extract_number());
+        }
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut solver = Mt19937Solver::new();
+        // 0x12340000..=0x1234FFFF share the top 16 bits.
+        solver.add_range(0, 0x12340000, 0x1234FFFF);
+        solver.add_range(1, 0, u32::MAX);
+        assert_eq!(solver.observations[0], (0, 0xFFFF0000, 0x12340000));
+        assert_eq!(solver.observations[1], (1, 0, 0));
+
+        // Contradictory observations.
+        solver.add_output(2, 5);
+        solver.add_output(2, 6);
+        assert!(solver.solve().is_none());
+    }
+}
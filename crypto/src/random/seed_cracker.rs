@@ -0,0 +1,189 @@
+//! Brute force of MT19937 seeds, for generators seeded with a timestamp or a small integer.
+//!
+//! Seeding and twisting cost about 1250 steps per seed, but the first output only depends on
+//! mt[0], mt[1] and mt[397] of the seeded state: when it's known, most seeds are rejected after
+//! 397 steps of the seeding recurrence. The search is split between threads, so the full 32 bits
+//! space takes minutes.
+
+use crate::random::Mt19937MersenneTwisterRng;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+const M: usize = 397;
+const F: u32 = 1812433253;
+const A: u32 = 0x9908B0DF;
+const UPPER_MASK: u32 = 0x80000000;
+/// Seeds handed out to a thread at a time.
+const CHUNK_SIZE: u64 = 1 << 14;
+
+type Predicate<'a> = Box<dyn Fn(&mut Mt19937MersenneTwisterRng) -> bool + Send + Sync + 'a>;
+
+/// The first output of `Mt19937MersenneTwisterRng::new_seed(seed)`, without seeding the whole
+/// state nor twisting it.
+pub fn mt19937_first_output(seed: u32) -> u32 {
+    let mut mt_1 = 0;
+    let mut x = seed;
+    for i in 1..=M as u32 {
+        x = F.wrapping_mul(x ^ (x >> 30)).wrapping_add(i);
+        if i == 1 {
+            mt_1 = x;
+        }
+    }
+    let y = (seed & UPPER_MASK) | (mt_1 & !UPPER_MASK);
+    let mut ya = y >> 1;
+    if y & 1 == 1 {
+        ya ^= A;
+    }
+    Mt19937MersenneTwisterRng::tamper((x ^ ya) as u64) as u32
+}
+
+/// Searches the seeds whose generator satisfies all the conditions.
+pub struct Mt19937SeedCracker<'a> {
+    first_output: Option<u32>,
+    predicates: Vec<Predicate<'a>>,
+    threads: usize,
+}
+
+impl Default for Mt19937SeedCracker<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Mt19937SeedCracker<'a> {
+    /// A cracker using all the available cores.
+    pub fn new() -> Self {
+        Self {
+            first_output: None,
+            predicates: vec![],
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// The first output of the generator is `output`: the cheap early rejection.
+    pub fn first_output(mut self, output: u32) -> Self {
+        self.first_output = Some(output);
+        self
+    }
+
+    /// A condition on the freshly seeded generator, e.g. "decrypts to a known suffix".
+    pub fn predicate(
+        mut self,
+        predicate: impl Fn(&mut Mt19937MersenneTwisterRng) -> bool + Send + Sync + 'a,
+    ) -> Self {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// The keystream, a byte per output made by `keystream`, has `known` at `offset`.
+    pub fn keystream(
+        self,
+        offset: usize,
+        known: &'a [u8],
+        keystream: impl Fn(&mut Mt19937MersenneTwisterRng) -> u8 + Send + Sync + 'a,
+    ) -> Self {
+        self.predicate(move |rng| {
+            for _ in 0..offset {
+                keystream(rng);
+            }
+            known.iter().all(|byte| keystream(rng) == *byte)
+        })
+    }
+
+    fn matches(&self, seed: u32) -> bool {
+        if let Some(output) = self.first_output {
+            if mt19937_first_output(seed) != output {
+                return false;
+            }
+        }
+        self.predicates
+            .iter()
+            .all(|predicate| predicate(&mut Mt19937MersenneTwisterRng::new_seed(seed)))
+    }
+
+    /// A seed of `seeds` matching the conditions, not necessarily the smallest one.
+    pub fn find(&self, seeds: RangeInclusive<u32>) -> Option<u32> {
+        let found = Mutex::new(None);
+        let stop = AtomicBool::new(false);
+        self.search(seeds, &stop, |seed| {
+            found.lock().unwrap().get_or_insert(seed);
+            stop.store(true, Ordering::Relaxed);
+        });
+        found.into_inner().unwrap()
+    }
+
+    /// All the seeds of `seeds` matching the conditions, sorted.
+    pub fn find_all(&self, seeds: RangeInclusive<u32>) -> Vec<u32> {
+        let found = Mutex::new(vec![]);
+        self.search(seeds, &AtomicBool::new(false), |seed| {
+            found.lock().unwrap().push(seed)
+        });
+        let mut found = found.into_inner().unwrap();
+        found.sort_unstable();
+        found
+    }
+
+    fn search(&self, seeds: RangeInclusive<u32>, stop: &AtomicBool, on_match: impl Fn(u32) + Sync) {
+        let (start, end) = (*seeds.start() as u64, *seeds.end() as u64);
+        if start > end {
+            return;
+        }
+        let next_chunk = AtomicU64::new(start);
+        thread::scope(|scope| {
+            for _ in 0..self.threads {
+                scope.spawn(|| {
+                    while !stop.load(Ordering::Relaxed) {
+                        let chunk = next_chunk.fetch_add(CHUNK_SIZE, Ordering::Relaxed);
+                        if chunk > end {
+                            break;
+                        }
+                        for seed in chunk..=end.min(chunk + CHUNK_SIZE - 1) {
+                            if self.matches(seed as u32) {
+                                on_match(seed as u32);
+                            }
+                        }
+                    }
+                });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::{mt19937_first_output, Mt19937MersenneTwisterRng, Mt19937SeedCracker};
+
+    #[test]
+    fn test_first_output() {
+        for seed in [0, 1, 5489, 0x80000001, u32::MAX] {
+            let mut rng = Mt19937MersenneTwisterRng::new_seed(seed);
+            assert_eq!(mt19937_first_output(seed), rng.extract_number());
+        }
+    }
+
+    #[test]
+    fn test_crack_first_output() {
+        let seed = 1_700_000_123;
+        let output = Mt19937MersenneTwisterRng::new_seed(seed).extract_number();
+        let cracker = Mt19937SeedCracker::new().first_output(output);
+        assert_eq!(cracker.find(1_700_000_000..=1_700_100_000), Some(seed));
+        assert_eq!(cracker.find(0..=1000), None);
+    }
+
+    #[test]
+    fn test_crack_16_bits_keystream() {
+        let seed = 0xbeef;
+        let mut rng = Mt19937MersenneTwisterRng::new_seed(seed);
+        let keystream: Vec<u8> = (0..30).map(|_| rng.extract_number() as u8).collect();
+        let cracker = Mt19937SeedCracker::new()
+            .keystream(20, &keystream[20..], |rng| rng.extract_number() as u8);
+        assert_eq!(cracker.find_all(0..=0xffff), vec![seed]);
+    }
+}
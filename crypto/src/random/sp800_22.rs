@@ -0,0 +1,477 @@
+//! Statistical tests from NIST SP 800-22 Rev. 1a, to check generators and keystreams: frequency,
+//! block frequency, runs, longest run of ones, serial, approximate entropy and cumulative sums.
+//!
+//! Each test returns p-values: the probability that a truly random sequence looks at least as
+//! non-random. The sequence fails a test when a p-value is below the significance level, 0.01
+//! in NIST's recommendations, so a good generator still fails 1% of the runs.
+//!
+//! https://csrc.nist.gov/publications/detail/sp/800-22/rev-1a/final
+
+use crate::random::Rng;
+use std::f64::consts::{LN_2, SQRT_2};
+
+/// The significance level recommended by NIST.
+pub const SIGNIFICANCE_LEVEL: f64 = 0.01;
+
+/// The shortest sequence `run_battery` accepts: the longest run test needs 128 bits.
+pub const MIN_BATTERY_BITS: usize = 128;
+
+/// The bits of `bytes`, most significant first.
+pub fn bits_from_bytes(bytes: impl IntoIterator<Item = u8>) -> Vec<bool> {
+    bytes
+        .into_iter()
+        .flat_map(|byte| (0..8).rev().map(move |bit| byte >> bit & 1 == 1))
+        .collect()
+}
+
+/// `n` bits of `rng`, from `fill_bytes`.
+pub fn bits_from_rng(rng: &mut impl Rng, n: usize) -> Vec<bool> {
+    let mut bytes = vec![0u8; n.div_ceil(8)];
+    rng.fill_bytes(&mut bytes);
+    let mut bits = bits_from_bytes(bytes);
+    bits.truncate(n);
+    bits
+}
+
+/// ln Γ(x) for x > 0, with the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    let x = x - 1.0;
+    let t = x + G + 0.5;
+    let series: f64 = COEFFICIENTS[0]
+        + COEFFICIENTS[1..]
+            .iter()
+            .enumerate()
+            .map(|(i, c)| c / (x + i as f64 + 1.0))
+            .sum::<f64>();
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// The regularized upper incomplete gamma function Q(a, x), NIST's igamc: a series below a + 1
+/// and a continued fraction above (Numerical Recipes 6.2).
+pub fn igamc(a: f64, x: f64) -> f64 {
+    const EPSILON: f64 = 1e-15;
+    const TINY: f64 = 1e-300;
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let prefactor = (-x + a * x.ln() - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut n = a;
+        while term.abs() > sum.abs() * EPSILON {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+        }
+        1.0 - sum * prefactor
+    } else {
+        // Modified Lentz's method.
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / TINY;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = b + an / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPSILON {
+                break;
+            }
+        }
+        prefactor * h
+    }
+}
+
+/// The complementary error function: erfc(x) = Q(1/2, x^2) for x >= 0.
+pub fn erfc(x: f64) -> f64 {
+    if x >= 0.0 {
+        igamc(0.5, x * x)
+    } else {
+        2.0 - igamc(0.5, x * x)
+    }
+}
+
+/// The cumulative distribution function of the standard normal distribution.
+fn normal_cdf(z: f64) -> f64 {
+    0.5 * erfc(-z / SQRT_2)
+}
+
+/// 2.1 Frequency (monobit): as many ones as zeros.
+pub fn frequency_test(bits: &[bool]) -> f64 {
+    assert!(!bits.is_empty(), "The frequency test needs bits");
+    let n = bits.len() as f64;
+    let sum: i64 = bits.iter().map(|b| if *b { 1 } else { -1 }).sum();
+    erfc(sum.abs() as f64 / n.sqrt() / SQRT_2)
+}
+
+/// 2.2 Frequency within blocks of `block_size` bits.
+pub fn block_frequency_test(bits: &[bool], block_size: usize) -> f64 {
+    assert!(
+        (1..=bits.len()).contains(&block_size),
+        "Blocks of {} bits in {} bits",
+        block_size,
+        bits.len()
+    );
+    let blocks = bits.len() / block_size;
+    let chi_squared: f64 = 4.0
+        * block_size as f64
+        * bits
+            .chunks_exact(block_size)
+            .map(|block| {
+                let ones = block.iter().filter(|b| **b).count();
+                (ones as f64 / block_size as f64 - 0.5).powi(2)
+            })
+            .sum::<f64>();
+    igamc(blocks as f64 / 2.0, chi_squared / 2.0)
+}
+
+/// 2.3 Runs: the number of uninterrupted sequences of identical bits.
+pub fn runs_test(bits: &[bool]) -> f64 {
+    assert!(!bits.is_empty(), "The runs test needs bits");
+    let n = bits.len() as f64;
+    let pi = bits.iter().filter(|b| **b).count() as f64 / n;
+    // The frequency test must pass first.
+    if (pi - 0.5).abs() >= 2.0 / n.sqrt() {
+        return 0.0;
+    }
+    let runs = 1 + bits.windows(2).filter(|w| w[0] != w[1]).count();
+    let expected = 2.0 * n * pi * (1.0 - pi);
+    erfc((runs as f64 - expected).abs() / (2.0 * (2.0 * n).sqrt() * pi * (1.0 - pi)))
+}
+
+/// 2.4 Longest run of ones in a block, for at least 128 bits. The block size and the classes
+/// depend on the length, following table 2.4.2.
+pub fn longest_run_test(bits: &[bool]) -> f64 {
+    let (block_size, first_class, probabilities): (usize, usize, &[f64]) = match bits.len() {
+        n if n >= 750_000 => (
+            10_000,
+            10,
+            &[0.0882, 0.2092, 0.2483, 0.1933, 0.1208, 0.0675, 0.0727],
+        ),
+        n if n >= 6272 => (128, 4, &[0.1174, 0.2430, 0.2493, 0.1752, 0.1027, 0.1124]),
+        n if n >= 128 => (8, 1, &[0.2148, 0.3672, 0.2305, 0.1875]),
+        _ => return 0.0,
+    };
+    let classes = probabilities.len();
+    let mut counts = vec![0usize; classes];
+    for block in bits.chunks_exact(block_size) {
+        let longest = block.split(|b| !*b).map(|run| run.len()).max().unwrap_or(0);
+        counts[longest.clamp(first_class, first_class + classes - 1) - first_class] += 1;
+    }
+    let blocks = (bits.len() / block_size) as f64;
+    let chi_squared: f64 = counts
+        .iter()
+        .zip(probabilities)
+        .map(|(count, p)| (*count as f64 - blocks * p).powi(2) / (blocks * p))
+        .sum();
+    igamc((classes - 1) as f64 / 2.0, chi_squared / 2.0)
+}
+
+/// The number of occurrences of each overlapping `m`-bit pattern, wrapping around the end.
+fn pattern_counts(bits: &[bool], m: usize) -> Vec<usize> {
+    assert!(
+        m <= bits.len(),
+        "Patterns of {} bits in {} bits",
+        m,
+        bits.len()
+    );
+    let mut counts = vec![0usize; 1 << m];
+    if m == 0 {
+        return counts;
+    }
+    let n = bits.len();
+    let mask = (1 << m) - 1;
+    let mut pattern = bits[n - (m - 1)..]
+        .iter()
+        .fold(0usize, |p, b| p << 1 | *b as usize);
+    for bit in bits {
+        pattern = (pattern << 1 | *bit as usize) & mask;
+        counts[pattern] += 1;
+    }
+    counts
+}
+
+/// 2.11 Serial: the frequency of all the overlapping `m`-bit patterns, m >= 3. Two p-values.
+pub fn serial_test(bits: &[bool], m: usize) -> (f64, f64) {
+    assert!(m >= 3, "The serial test needs patterns of at least 3 bits");
+    let n = bits.len() as f64;
+    let psi_squared = |m: usize| {
+        if m == 0 {
+            return 0.0;
+        }
+        let sum: f64 = pattern_counts(bits, m)
+            .iter()
+            .map(|c| (*c as f64).powi(2))
+            .sum();
+        (1u64 << m) as f64 / n * sum - n
+    };
+    let (psi_m, psi_m1, psi_m2) = (psi_squared(m), psi_squared(m - 1), psi_squared(m - 2));
+    let delta = psi_m - psi_m1;
+    let delta_2 = psi_m - 2.0 * psi_m1 + psi_m2;
+    (
+        igamc((1u64 << (m - 2)) as f64, delta / 2.0),
+        igamc((1u64 << (m - 3)) as f64, delta_2 / 2.0),
+    )
+}
+
+/// 2.12 Approximate entropy: the frequencies of the overlapping `m` and `m + 1`-bit patterns,
+/// m >= 1.
+pub fn approximate_entropy_test(bits: &[bool], m: usize) -> f64 {
+    assert!(
+        m >= 1,
+        "The approximate entropy test needs patterns of at least 1 bit"
+    );
+    let n = bits.len() as f64;
+    let phi = |m: usize| -> f64 {
+        pattern_counts(bits, m)
+            .iter()
+            .filter(|c| **c > 0)
+            .map(|c| {
+                let p = *c as f64 / n;
+                p * p.ln()
+            })
+            .sum()
+    };
+    let approximate_entropy = phi(m) - phi(m + 1);
+    let chi_squared = 2.0 * n * (LN_2 - approximate_entropy);
+    igamc((1u64 << (m - 1)) as f64, chi_squared / 2.0)
+}
+
+/// 2.13 Cumulative sums: the largest excursion of the random walk of the ±1 bits, from the
+/// start and from the end. Two p-values, forward and backward.
+pub fn cumulative_sums_test(bits: &[bool]) -> (f64, f64) {
+    assert!(!bits.is_empty(), "The cumulative sums test needs bits");
+    let n = bits.len() as f64;
+    let p_value = |bits: &mut dyn Iterator<Item = &bool>| {
+        let mut sum = 0i64;
+        let mut z = 0i64;
+        for bit in bits {
+            sum += if *bit { 1 } else { -1 };
+            z = z.max(sum.abs());
+        }
+        let z = z as f64;
+        let sqrt_n = n.sqrt();
+        let mut p = 1.0;
+        let start = ((-n / z + 1.0) / 4.0).floor() as i64;
+        let end = ((n / z - 1.0) / 4.0).floor() as i64;
+        for k in start..=end {
+            let k = k as f64;
+            p -=
+                normal_cdf((4.0 * k + 1.0) * z / sqrt_n) - normal_cdf((4.0 * k - 1.0) * z / sqrt_n);
+        }
+        let start = ((-n / z - 3.0) / 4.0).floor() as i64;
+        for k in start..=end {
+            let k = k as f64;
+            p +=
+                normal_cdf((4.0 * k + 3.0) * z / sqrt_n) - normal_cdf((4.0 * k + 1.0) * z / sqrt_n);
+        }
+        p
+    };
+    (p_value(&mut bits.iter()), p_value(&mut bits.iter().rev()))
+}
+
+/// The p-value of one test of the battery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    pub test: &'static str,
+    pub p_value: f64,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.p_value >= SIGNIFICANCE_LEVEL
+    }
+}
+
+/// Runs all the tests, with parameters chosen from the length as NIST recommends. Panics below
+/// `MIN_BATTERY_BITS`, and needs at least a few thousand bits to be meaningful; 10^6 is NIST's
+/// usual length.
+pub fn run_battery(bits: &[bool]) -> Vec<TestResult> {
+    let n = bits.len();
+    assert!(
+        n >= MIN_BATTERY_BITS,
+        "The battery needs at least {} bits, got {}",
+        MIN_BATTERY_BITS,
+        n
+    );
+    let log_n = n.ilog2() as usize;
+    // M >= 20, M > n / 100 and fewer than 100 blocks.
+    let block_size = (n / 99).max(20);
+    // m < log2(n) - 2 for the serial test, m < log2(n) - 5 for the approximate entropy.
+    let serial_m = log_n.saturating_sub(3).clamp(3, 16);
+    let entropy_m = log_n.saturating_sub(6).clamp(2, 10);
+
+    let (serial_1, serial_2) = serial_test(bits, serial_m);
+    let (forward, backward) = cumulative_sums_test(bits);
+    [
+        ("frequency", frequency_test(bits)),
+        ("block frequency", block_frequency_test(bits, block_size)),
+        ("runs", runs_test(bits)),
+        ("longest run", longest_run_test(bits)),
+        ("serial 1", serial_1),
+        ("serial 2", serial_2),
+        (
+            "approximate entropy",
+            approximate_entropy_test(bits, entropy_m),
+        ),
+        ("cumulative sums forward", forward),
+        ("cumulative sums backward", backward),
+    ]
+    .iter()
+    .map(|(test, p_value)| TestResult {
+        test,
+        p_value: *p_value,
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::sp800_22::*;
+    use crate::random::Mt19937MersenneTwisterRng;
+
+    fn bits(s: &str) -> Vec<bool> {
+        s.chars().map(|c| c == '1').collect()
+    }
+
+    /// The 100 bits of the examples of section 2.
+    const EPSILON: &str = "11001001000011111101101010100010001000010110100011\
+                           00001000110100110001001100011001100010100010111000";
+
+    fn assert_p_value(received: f64, expected: f64) {
+        assert!(
+            (received - expected).abs() < 1e-6,
+            "{} != {}",
+            received,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_special_functions() {
+        assert_p_value(erfc(0.0), 1.0);
+        assert_p_value(erfc(1.0), 0.157299);
+        assert_p_value(erfc(-1.0), 1.842701);
+        assert_p_value(igamc(1.0, 2.0), (-2.0f64).exp());
+        assert_p_value(igamc(3.0, 10.0), 0.002769);
+    }
+
+    #[test]
+    fn test_nist_examples() {
+        let epsilon = bits(EPSILON);
+        assert_p_value(frequency_test(&epsilon), 0.109599);
+        assert_p_value(block_frequency_test(&epsilon, 10), 0.706438);
+        assert_p_value(runs_test(&epsilon), 0.500798);
+        assert_p_value(approximate_entropy_test(&epsilon, 2), 0.235301);
+        let (forward, backward) = cumulative_sums_test(&epsilon);
+        assert_p_value(forward, 0.219194);
+        assert_p_value(backward, 0.114866);
+
+        let (p1, p2) = serial_test(&bits("0011011101"), 3);
+        assert_p_value(p1, 0.808792);
+        assert_p_value(p2, 0.670320);
+        assert_p_value(approximate_entropy_test(&bits("0100110101"), 3), 0.261961);
+
+        let epsilon = bits(
+            "11001100000101010110110001001100111000000000001001001101010100010001001111010110\
+             100000001101011111001100111001101101100010110010",
+        );
+        // The spec prints 0.180609 with its own igamc, Q(3/2, x) in closed form gives 0.180598.
+        assert_p_value(longest_run_test(&epsilon), 0.180598);
+    }
+
+    #[test]
+    fn test_battery() {
+        // Even a perfect generator fails 1% of the tests, so look at the proportion of failures
+        // over several sequences like NIST does: about 1 in the 90 here.
+        let mut failures = 0;
+        for seed in 0..10 {
+            let mut rng = Mt19937MersenneTwisterRng::new_seed(seed);
+            let results = run_battery(&bits_from_rng(&mut rng, 100_000));
+            assert_eq!(results.len(), 9);
+            failures += results.iter().filter(|r| !r.passed()).count();
+        }
+        assert!(failures <= 4, "{} failures", failures);
+
+        // The top bit of every byte stuck at 0.
+        let mut rng = Mt19937MersenneTwisterRng::new_seed(5489);
+        let mut stuck = bits_from_rng(&mut rng, 100_000);
+        for bit in stuck.iter_mut().step_by(8) {
+            *bit = false;
+        }
+        let results = run_battery(&stuck);
+        assert!(!results.iter().all(TestResult::passed), "{:?}", results);
+
+        // The first keystream of ex_24, `(rand() * 100.0) as u8`: bytes below 100.
+        let mut rng = Mt19937MersenneTwisterRng::new_seed(5489);
+        let biased = bits_from_bytes((0..12_500).map(|_| (rng.rand() * 100.0) as u8));
+        let results = run_battery(&biased);
+        assert!(!results.iter().all(TestResult::passed), "{:?}", results);
+    }
+
+    #[test]
+    fn test_short_input() {
+        let mut rng = Mt19937MersenneTwisterRng::new_seed(1);
+        let results = run_battery(&bits_from_rng(&mut rng, MIN_BATTERY_BITS));
+        assert_eq!(results.len(), 9);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 128 bits")]
+    fn test_battery_too_short() {
+        run_battery(&bits("0110"));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 3 bits")]
+    fn test_serial_too_short_patterns() {
+        serial_test(&bits(EPSILON), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 1 bit")]
+    fn test_approximate_entropy_empty_patterns() {
+        approximate_entropy_test(&bits(EPSILON), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Patterns of 5 bits in 4 bits")]
+    fn test_patterns_longer_than_input() {
+        serial_test(&bits("0110"), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "The frequency test needs bits")]
+    fn test_frequency_empty() {
+        frequency_test(&[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Blocks of 0 bits in 100 bits")]
+    fn test_block_frequency_empty_blocks() {
+        block_frequency_test(&bits(EPSILON), 0);
+    }
+}
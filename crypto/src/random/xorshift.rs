@@ -0,0 +1,439 @@
+//! xorshift128+ (the `Math.random` of V8, SpiderMonkey and JavaScriptCore) and xoshiro256**,
+//! with state recovery from their outputs.
+//!
+//! Both state transitions are linear over GF(2), so like MT19937 each output bit that is a
+//! linear function of the state gives an equation, and enough of them give the state:
+//! - V8 doubles are the top 52 bits of state0: 4 doubles are enough (3 leave 12 bits unknown).
+//! - xorshift128+ integers are state0 + state1: only the lowest bit of the sum is linear, so it
+//!   takes 128 consecutive outputs.
+//! - xoshiro256** integers are an invertible function of s[1]: 4 consecutive outputs are enough,
+//!   outputs with gaps may need more.
+//!
+//! The rows of the equations come from running the generator from each unit state: by linearity,
+//! output bit b of the state x is the xor of bit b for the unit states of the bits set in x.
+
+use crate::random::gf2::{BitVector, LinearSystem};
+use crate::random::{impl_rand_core, Rng};
+
+/// The exponent of the doubles in [1, 2).
+const EXPONENT_BITS: u64 = 0x3FF0000000000000;
+const MANTISSA_BITS: u32 = 52;
+
+/// Initialization of V8 and of the xoshiro seeding, spreading the seed bits.
+fn murmur_hash3(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xFF51AFD7ED558CCD);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xC4CEB9FE1A85EC53);
+    h ^= h >> 33;
+    h
+}
+
+/// The recommended seeding of xoshiro generators.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Solves the equations, `None` if they contradict each other or don't determine all the
+/// unknowns.
+fn solve_linear(
+    unknowns: usize,
+    equations: impl IntoIterator<Item = (BitVector, bool)>,
+) -> Option<BitVector> {
+    let mut system = LinearSystem::new(unknowns);
+    for (row, value) in equations {
+        if !system.add_equation(row, value) {
+            return None;
+        }
+    }
+    (system.rank() == unknowns).then(|| system.solve())
+}
+
+/// The row of bit `bit` of a word, given its value for each unit state.
+fn row(words: &[u64], bit: u32) -> BitVector {
+    let mut ret = BitVector::zeros(words.len());
+    for (unknown, word) in words.iter().enumerate() {
+        if word >> bit & 1 == 1 {
+            ret.flip(unknown);
+        }
+    }
+    ret
+}
+
+/// xorshift128+ with V8's shifts (23, 17, 26).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xorshift128Plus {
+    pub state0: u64,
+    pub state1: u64,
+}
+
+impl Xorshift128Plus {
+    /// V8's seeding from `--random-seed`.
+    pub fn new_seed(seed: u64) -> Self {
+        let state0 = murmur_hash3(seed);
+        Self {
+            state0,
+            state1: murmur_hash3(!state0),
+        }
+    }
+
+    fn step(&mut self) {
+        let mut s1 = self.state0;
+        let s0 = self.state1;
+        self.state0 = s0;
+        s1 ^= s1 << 23;
+        s1 ^= s1 >> 17;
+        s1 ^= s0;
+        s1 ^= s0 >> 26;
+        self.state1 = s1;
+    }
+
+    /// The xorshift128+ output: the sum of the state words, then a step.
+    pub fn extract_number(&mut self) -> u64 {
+        let ret = self.state0.wrapping_add(self.state1);
+        self.step();
+        ret
+    }
+
+    /// A double in [0, 1) the way V8 makes them: a step, then the top 52 bits of state0.
+    /// Note that `Math.random` hands out V8's cache of 64 doubles from the end, so consecutive
+    /// calls are in reverse order within a block.
+    pub fn next_f64(&mut self) -> f64 {
+        self.step();
+        f64::from_bits(self.state0 >> 12 | EXPONENT_BITS) - 1.0
+    }
+
+    /// Clones a generator from its next 128 outputs, like
+    /// `Mt19937MersenneTwisterRng::clone_from_output`.
+    pub fn clone_from_output(original: &mut Self) -> Self {
+        let mut solver = Xorshift128PlusSolver::new();
+        for position in 0..128 {
+            solver.add_output(position, original.extract_number());
+        }
+        let mut ret = solver.solve().expect("128 outputs determine the state");
+        for _ in 0..128 {
+            ret.extract_number();
+        }
+        ret
+    }
+}
+
+impl Default for Xorshift128Plus {
+    fn default() -> Self {
+        Self::new_seed(0)
+    }
+}
+
+impl Rng for Xorshift128Plus {
+    /// The high half of an output.
+    fn next_u32(&mut self) -> u32 {
+        (self.extract_number() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.extract_number()
+    }
+}
+impl_rand_core!(Xorshift128Plus, u64);
+
+#[derive(Debug, Clone, Copy)]
+enum Xorshift128PlusObservation {
+    Output(u64),
+    Double(f64),
+}
+
+/// Observations of a xorshift128+, counted in calls from position 0.
+#[derive(Debug, Clone, Default)]
+pub struct Xorshift128PlusSolver {
+    observations: Vec<(usize, Xorshift128PlusObservation)>,
+}
+
+impl Xorshift128PlusSolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The `extract_number` call at `position` returned `output`.
+    pub fn add_output(&mut self, position: usize, output: u64) {
+        self.observations
+            .push((position, Xorshift128PlusObservation::Output(output)));
+    }
+
+    /// The `next_f64` call at `position` returned `double`.
+    pub fn add_double(&mut self, position: usize, double: f64) {
+        self.observations
+            .push((position, Xorshift128PlusObservation::Double(double)));
+    }
+
+    /// The generator at position 0, or `None` if the observations are inconsistent or not
+    /// enough.
+    pub fn solve(&self) -> Option<Xorshift128Plus> {
+        let steps = self.observations.iter().map(|(p, _)| p + 2).max()?;
+        // states[t][j]: the state after t steps, starting from the unit state j.
+        let mut units: Vec<Xorshift128Plus> = (0..128)
+            .map(|j| Xorshift128Plus {
+                state0: if j < 64 { 1 << j } else { 0 },
+                state1: if j < 64 { 0 } else { 1 << (j - 64) },
+            })
+            .collect();
+        let mut states = vec![units.clone()];
+        for _ in 1..steps {
+            units.iter_mut().for_each(Xorshift128Plus::step);
+            states.push(units.clone());
+        }
+
+        let mut equations = vec![];
+        for (position, observation) in &self.observations {
+            match observation {
+                Xorshift128PlusObservation::Output(output) => {
+                    // The lowest bit of a sum is the xor of the lowest bits.
+                    let words: Vec<u64> = states[*position]
+                        .iter()
+                        .map(|s| s.state0 ^ s.state1)
+                        .collect();
+                    equations.push((row(&words, 0), output & 1 == 1));
+                }
+                Xorshift128PlusObservation::Double(double) => {
+                    let mantissa = (double + 1.0).to_bits();
+                    let words: Vec<u64> = states[position + 1].iter().map(|s| s.state0).collect();
+                    for bit in 0..MANTISSA_BITS {
+                        equations.push((row(&words, bit + 12), mantissa >> bit & 1 == 1));
+                    }
+                }
+            }
+        }
+        let solution = solve_linear(128, equations)?;
+        let word =
+            |offset: usize| (0..64).fold(0u64, |w, b| w | (solution.get(offset + b) as u64) << b);
+        let ret = Xorshift128Plus {
+            state0: word(0),
+            state1: word(64),
+        };
+
+        // The integers only gave their lowest bit.
+        let mut sorted = self.observations.clone();
+        sorted.sort_by_key(|(position, _)| *position);
+        let mut rng = ret.clone();
+        let mut position = 0;
+        for (observed, observation) in sorted {
+            while position < observed {
+                rng.step();
+                position += 1;
+            }
+            let matches = match observation {
+                Xorshift128PlusObservation::Output(output) => {
+                    rng.clone().extract_number() == output
+                }
+                Xorshift128PlusObservation::Double(double) => rng.clone().next_f64() == double,
+            };
+            if !matches {
+                return None;
+            }
+        }
+        Some(ret)
+    }
+}
+
+/// xoshiro256**, the default generator of many languages (.NET 6, Lua 5.4, ...).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xoshiro256StarStar {
+    pub s: [u64; 4],
+}
+
+impl Xoshiro256StarStar {
+    /// Seeds the state with splitmix64, as recommended by the authors.
+    pub fn new_seed(seed: u64) -> Self {
+        let mut state = seed;
+        Self {
+            s: std::array::from_fn(|_| splitmix64(&mut state)),
+        }
+    }
+
+    fn step(&mut self) {
+        let s = &mut self.s;
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+    }
+
+    pub fn extract_number(&mut self) -> u64 {
+        let ret = Self::scramble(self.s[1]);
+        self.step();
+        ret
+    }
+
+    fn scramble(s1: u64) -> u64 {
+        s1.wrapping_mul(5).rotate_left(7).wrapping_mul(9)
+    }
+
+    /// The s[1] giving `output`: 5 and 9 are invertible modulo 2^64.
+    fn unscramble(output: u64) -> u64 {
+        const INVERSE_9: u64 = 0x8E38E38E38E38E39;
+        const INVERSE_5: u64 = 0xCCCCCCCCCCCCCCCD;
+        output
+            .wrapping_mul(INVERSE_9)
+            .rotate_right(7)
+            .wrapping_mul(INVERSE_5)
+    }
+
+    /// Clones a generator from its next 4 outputs, like
+    /// `Mt19937MersenneTwisterRng::clone_from_output`.
+    pub fn clone_from_output(original: &mut Self) -> Self {
+        let mut solver = Xoshiro256StarStarSolver::new();
+        for position in 0..4 {
+            solver.add_output(position, original.extract_number());
+        }
+        let mut ret = solver.solve().expect("4 outputs determine the state");
+        for _ in 0..4 {
+            ret.extract_number();
+        }
+        ret
+    }
+}
+
+impl Default for Xoshiro256StarStar {
+    fn default() -> Self {
+        Self::new_seed(0)
+    }
+}
+
+impl Rng for Xoshiro256StarStar {
+    /// The high half of an output.
+    fn next_u32(&mut self) -> u32 {
+        (self.extract_number() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.extract_number()
+    }
+}
+impl_rand_core!(Xoshiro256StarStar, u64);
+
+/// Outputs of a xoshiro256**, counted in calls from position 0.
+#[derive(Debug, Clone, Default)]
+pub struct Xoshiro256StarStarSolver {
+    outputs: Vec<(usize, u64)>,
+}
+
+impl Xoshiro256StarStarSolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The `extract_number` call at `position` returned `output`.
+    pub fn add_output(&mut self, position: usize, output: u64) {
+        self.outputs.push((position, output));
+    }
+
+    /// The generator at position 0, or `None` if the outputs are inconsistent or not enough.
+    pub fn solve(&self) -> Option<Xoshiro256StarStar> {
+        let steps = self.outputs.iter().map(|(p, _)| p + 1).max()?;
+        let mut units: Vec<Xoshiro256StarStar> = (0..256)
+            .map(|j| {
+                let mut s = [0u64; 4];
+                s[j / 64] = 1 << (j % 64);
+                Xoshiro256StarStar { s }
+            })
+            .collect();
+        let mut states = vec![units.clone()];
+        for _ in 1..steps {
+            units.iter_mut().for_each(Xoshiro256StarStar::step);
+            states.push(units.clone());
+        }
+
+        let mut equations = vec![];
+        for (position, output) in &self.outputs {
+            let s1 = Xoshiro256StarStar::unscramble(*output);
+            let words: Vec<u64> = states[*position].iter().map(|s| s.s[1]).collect();
+            for bit in 0..64 {
+                equations.push((row(&words, bit), s1 >> bit & 1 == 1));
+            }
+        }
+        let solution = solve_linear(256, equations)?;
+        Some(Xoshiro256StarStar {
+            s: std::array::from_fn(|i| {
+                (0..64).fold(0u64, |w, b| w | (solution.get(i * 64 + b) as u64) << b)
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::{
+        Xorshift128Plus, Xorshift128PlusSolver, Xoshiro256StarStar, Xoshiro256StarStarSolver,
+    };
+
+    #[test]
+    fn test_xoshiro_reference() {
+        // The reference implementation from the state [1, 2, 3, 4].
+        let mut rng = Xoshiro256StarStar { s: [1, 2, 3, 4] };
+        let outputs: Vec<u64> = (0..4).map(|_| rng.extract_number()).collect();
+        assert_eq!(outputs, vec![11520, 0, 1509978240, 1215971899390074240]);
+        assert_eq!(
+            Xoshiro256StarStar::unscramble(Xoshiro256StarStar::scramble(0xdeadbeef)),
+            0xdeadbeef
+        );
+    }
+
+    #[test]
+    fn test_clone_from_output() {
+        let mut rng = Xorshift128Plus::new_seed(42);
+        let mut cloned = Xorshift128Plus::clone_from_output(&mut rng);
+        for _ in 0..1000 {
+            assert_eq!(rng.extract_number(), cloned.extract_number());
+        }
+
+        let mut rng = Xoshiro256StarStar::new_seed(42);
+        let mut cloned = Xoshiro256StarStar::clone_from_output(&mut rng);
+        for _ in 0..1000 {
+            assert_eq!(rng.extract_number(), cloned.extract_number());
+        }
+    }
+
+    #[test]
+    fn test_v8_doubles() {
+        let mut rng = Xorshift128Plus::new_seed(1337);
+        let original = rng.clone();
+        let doubles: Vec<f64> = (0..64).map(|_| rng.next_f64()).collect();
+        assert!(doubles.iter().all(|d| (0.0..1.0).contains(d)));
+
+        // Math.random returned the end of the cache first: put them back in order.
+        let mut observed = doubles.clone();
+        observed.reverse();
+        let mut solver = Xorshift128PlusSolver::new();
+        for (i, double) in observed[..4].iter().enumerate() {
+            solver.add_double(63 - i, *double);
+        }
+        assert_eq!(solver.solve(), Some(original));
+    }
+
+    #[test]
+    fn test_not_enough_observations() {
+        let mut rng = Xorshift128Plus::new_seed(1);
+        let mut solver = Xorshift128PlusSolver::new();
+        solver.add_double(0, rng.next_f64());
+        solver.add_double(1, rng.next_f64());
+        assert_eq!(solver.solve(), None);
+
+        // Outputs with gaps: 4 outputs aren't always enough, here 5 are.
+        let mut rng = Xoshiro256StarStar::new_seed(1);
+        let original = rng.clone();
+        let outputs: Vec<u64> = (0..15).map(|_| rng.extract_number()).collect();
+        let mut solver = Xoshiro256StarStarSolver::new();
+        for position in [0, 2, 5, 9] {
+            solver.add_output(position, outputs[position]);
+        }
+        assert!(solver.solve().is_none());
+        solver.add_output(14, outputs[14]);
+        assert_eq!(solver.solve(), Some(original));
+    }
+}
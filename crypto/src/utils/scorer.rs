@@ -0,0 +1,210 @@
+//! Language models to rank candidate plaintexts, e.g. the 256 decryptions of a single byte xor.
+//! The lower the score, the more the text looks like the corpus the model was trained on.
+//!
+//! The models are case sensitive and count spaces and punctuation: a key off by 0x20 gives the
+//! same letters with the wrong case, and only the case tells them apart. Whitespace runs of the
+//! corpus count as a single space, its line breaks are only layout.
+
+use std::collections::HashMap;
+
+/// The first three chapters of Alice's Adventures in Wonderland, from the Canterbury corpus.
+pub const ENGLISH_CORPUS: &str = include_str!("../../res/alice.txt");
+
+pub trait Scorer {
+    /// Lower is better. Only comparable between texts of the same length.
+    fn score(&self, text: &[u8]) -> f64;
+}
+
+fn normalize(corpus: &[u8]) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(corpus.len());
+    for byte in corpus {
+        if byte.is_ascii_whitespace() {
+            if ret.last() != Some(&b' ') {
+                ret.push(b' ');
+            }
+        } else {
+            ret.push(*byte);
+        }
+    }
+    ret
+}
+
+/// Pearson's chi-squared test of the byte frequencies against the corpus ones. The counts of the
+/// corpus are smoothed by one, so a byte it never uses costs a lot without being impossible.
+///
+/// A single occurrence of a byte of probability p costs about 1 / (n p): on short texts, the
+/// rare bytes decide. `NgramScorer` with unigrams weighs them less.
+pub struct ChiSquaredScorer {
+    probabilities: [f64; 256],
+}
+
+impl ChiSquaredScorer {
+    pub fn train(corpus: &[u8]) -> Self {
+        let mut counts = [1.0; 256];
+        for byte in normalize(corpus) {
+            counts[byte as usize] += 1.0;
+        }
+        let total: f64 = counts.iter().sum();
+        Self {
+            probabilities: counts.map(|count| count / total),
+        }
+    }
+
+    pub fn english() -> Self {
+        Self::train(ENGLISH_CORPUS.as_bytes())
+    }
+}
+
+impl Scorer for ChiSquaredScorer {
+    fn score(&self, text: &[u8]) -> f64 {
+        let mut counts = [0usize; 256];
+        for byte in text {
+            counts[*byte as usize] += 1;
+        }
+        let n = text.len() as f64;
+        counts
+            .iter()
+            .zip(&self.probabilities)
+            .map(|(count, p)| (*count as f64 - n * p).powi(2) / (n * p))
+            .sum()
+    }
+}
+
+/// The negative log-likelihood of the overlapping n-grams, averaged. Beyond unigrams, it needs the
+/// bytes in the order of the text: not suited to the columns of a repeating key.
+pub struct NgramScorer {
+    n: usize,
+    log_probabilities: HashMap<Vec<u8>, f64>,
+    /// For the n-grams missing from the corpus: a hundredth of one occurrence.
+    unseen: f64,
+}
+
+impl NgramScorer {
+    pub fn train(corpus: &[u8], n: usize) -> Self {
+        assert!(n > 0, "n-grams of at least one byte");
+        let corpus = normalize(corpus);
+        let mut counts: HashMap<Vec<u8>, usize> = HashMap::new();
+        for gram in corpus.windows(n) {
+            *counts.entry(gram.to_vec()).or_default() += 1;
+        }
+        let total = corpus.len().saturating_sub(n - 1).max(1) as f64;
+        Self {
+            n,
+            log_probabilities: counts
+                .into_iter()
+                .map(|(gram, count)| (gram, (count as f64 / total).ln()))
+                .collect(),
+            unseen: (0.01 / total).ln(),
+        }
+    }
+
+    pub fn english_unigrams() -> Self {
+        Self::train(ENGLISH_CORPUS.as_bytes(), 1)
+    }
+
+    pub fn english_bigrams() -> Self {
+        Self::train(ENGLISH_CORPUS.as_bytes(), 2)
+    }
+
+    pub fn english_trigrams() -> Self {
+        Self::train(ENGLISH_CORPUS.as_bytes(), 3)
+    }
+}
+
+impl Scorer for NgramScorer {
+    /// Texts shorter than n all score 0.
+    fn score(&self, text: &[u8]) -> f64 {
+        let grams = text.len().saturating_sub(self.n - 1);
+        if grams == 0 {
+            return 0.0;
+        }
+        -text
+            .windows(self.n)
+            .map(|gram| *self.log_probabilities.get(gram).unwrap_or(&self.unseen))
+            .sum::<f64>()
+            / grams as f64
+    }
+}
+
+/// The proportion of bytes the corpus never uses: control characters, non-ASCII bytes, and
+/// whatever else a plaintext can't contain. Only rules out garbage, it doesn't rank text.
+pub struct PrintableScorer {
+    allowed: [bool; 256],
+}
+
+impl PrintableScorer {
+    /// The bytes of `corpus` are allowed, and the whitespace.
+    pub fn train(corpus: &[u8]) -> Self {
+        let mut allowed = [false; 256];
+        for byte in corpus.iter().chain(b" \t\r\n") {
+            allowed[*byte as usize] = true;
+        }
+        Self { allowed }
+    }
+
+    /// Printable ASCII and whitespace.
+    pub fn ascii() -> Self {
+        Self::train(&(0x20..0x7f).collect::<Vec<u8>>())
+    }
+}
+
+impl Scorer for PrintableScorer {
+    fn score(&self, text: &[u8]) -> f64 {
+        let penalties = text
+            .iter()
+            .filter(|byte| !self.allowed[**byte as usize])
+            .count();
+        penalties as f64 / text.len().max(1) as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::{ChiSquaredScorer, NgramScorer, PrintableScorer, Scorer};
+
+    const TEXT: &[u8] = b"Now that the party is jumping";
+
+    fn flip_case(text: &[u8]) -> Vec<u8> {
+        text.iter()
+            .map(|b| {
+                if b.is_ascii_alphabetic() {
+                    b ^ 0x20
+                } else {
+                    *b
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_prefers_english() {
+        let scorers: [Box<dyn Scorer>; 4] = [
+            Box::new(ChiSquaredScorer::english()),
+            Box::new(NgramScorer::english_unigrams()),
+            Box::new(NgramScorer::english_bigrams()),
+            Box::new(NgramScorer::english_trigrams()),
+        ];
+        // Shuffled letters, the wrong case, a key off by one bit.
+        let shuffled = b"wNo ttah eht ytrap si pmujgin";
+        let garbled: Vec<u8> = TEXT.iter().map(|b| b ^ 1).collect();
+        for scorer in &scorers {
+            let score = scorer.score(TEXT);
+            assert!(score < scorer.score(&flip_case(TEXT)));
+            assert!(score < scorer.score(&garbled));
+            assert!(score < scorer.score(&b"\x01\x02\x03".repeat(10)[..29]));
+        }
+        for scorer in &scorers[2..] {
+            assert!(scorer.score(TEXT) < scorer.score(shuffled));
+        }
+    }
+
+    #[test]
+    fn test_printable() {
+        let scorer = PrintableScorer::ascii();
+        assert_eq!(scorer.score(TEXT), 0.0);
+        assert_eq!(scorer.score(b"ab\x00\xff"), 0.5);
+        // The only digits of the corpus are those of "EDITION 2.9".
+        let scorer = PrintableScorer::train(crate::utils::ENGLISH_CORPUS.as_bytes());
+        assert_eq!(scorer.score(b"1299"), 0.25);
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod test {
-    use crate::shared::single_byte_xor_dechiper;
+    use crate::shared::{single_byte_xor_dechiper, ChiSquaredScorer};
     use hex_literal::hex;
 
     #[test]
@@ -8,7 +8,7 @@ mod test {
         let input = hex!("1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736");
         let expected = b"Cooking MC's like a pound of bacon";
         assert_eq!(
-            single_byte_xor_dechiper(input.to_vec()).1,
+            single_byte_xor_dechiper(input.to_vec(), &ChiSquaredScorer::english()).1,
             expected.to_vec()
         );
     }
@@ -1,13 +1,14 @@
-use crate::shared::single_byte_xor_dechiper;
+use crate::shared::{single_byte_xor_dechiper, ChiSquaredScorer};
 use std::path::PathBuf;
 
 fn detect_single_character_xor(file: PathBuf) -> Vec<u8> {
     let file = std::fs::read_to_string(&file).unwrap();
+    let scorer = ChiSquaredScorer::english();
     file.split('\n')
         .into_iter()
         .map(hex::decode)
         .filter_map(Result::ok)
-        .map(single_byte_xor_dechiper)
+        .map(|line| single_byte_xor_dechiper(line, &scorer))
         .fold((f64::MAX, Vec::new(), 0), |curr, new| {
             if curr.0 > new.0 {
                 new
@@ -45,9 +45,10 @@ fn find_key_size(crypted: &[u8]) -> Vec<(usize, f32)> {
 }
 
 fn find_original_key(transposed: Vec<Vec<u8>>) -> Vec<u8> {
+    let scorer = shared::ChiSquaredScorer::english();
     transposed
         .into_iter()
-        .map(|t| shared::single_byte_xor_dechiper(t).2)
+        .map(|t| shared::single_byte_xor_dechiper(t, &scorer).2)
         .collect()
 }
 
@@ -46,7 +46,10 @@ const INDEPENDENT_PT: &[&str] = &[
 
 pub fn solve(ciphertexts: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
     let transposed = crypto::utils::transpose(ciphertexts.clone());
-    let (_, key) = crypto::utils::find_original_key(transposed.clone());
+    // The byte frequencies by log-likelihood: the chi-squared overreacts to the few bytes
+    // the corpus hardly uses, like the quotes and the capitals of ex 20.
+    let scorer = crypto::utils::NgramScorer::english_unigrams();
+    let (_, key) = crypto::utils::find_original_key(transposed.clone(), &scorer);
     let mut ret = vec![];
     for i in ciphertexts {
         ret.push(crypto::utils::repeating_xor_key(&i, key.clone()));
@@ -72,10 +75,12 @@ mod test {
             let received = solve(ciphertexts);
 
             // Longer lines are not fully recovered. I should try to guess / match the last word with a dictionary.
+            // The first column only holds capitals: the byte frequencies of running text prefer
+            // the key giving lowercase letters.
 
             assert_eq!(
                 String::from_utf8_lossy(&received[0]),
-                "i have met them at close nf day"
+                "i have met them at close of day"
             );
             assert_eq!(
                 String::from_utf8_lossy(&received[1]),
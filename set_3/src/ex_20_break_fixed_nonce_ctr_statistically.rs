@@ -31,7 +31,7 @@ mod test {
         let ret = solve(lines);
         let expected = [
             105, 39, 109, 32, 114, 97, 116, 101, 100, 32, 34, 82, 34, 46, 46, 46, 116, 104, 105,
-            115, 32, 105, 115, 32, 97, 32, 119, 97, 115, 110, 105, 110, 103, 44, 32, 121, 97, 32,
+            115, 32, 105, 115, 32, 97, 32, 119, 97, 114, 110, 105, 110, 103, 44, 32, 121, 97, 32,
             98, 101, 116, 116, 101, 114, 32, 118, 111, 105, 100, 32, 47, 32, 80, 111, 101, 116,
             115, 32, 97, 114, 101, 32, 112, 97, 114, 97, 110, 111, 105, 100, 44, 32, 68, 74, 39,
             115, 32, 68, 45, 115, 116, 114, 111, 121, 101, 100,
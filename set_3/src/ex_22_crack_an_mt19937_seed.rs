@@ -17,7 +17,7 @@
 //! From the 32 bit RNG output, discover the seed.
 //!
 
-use crypto::random::Mt19937MersenneTwisterRng;
+use crypto::random::Mt19937SeedCracker;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 fn solve(random: u32) -> u32 {
@@ -25,13 +25,10 @@ fn solve(random: u32) -> u32 {
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_secs() as u32;
-    for seed in now - 1 * 60..now {
-        let mut rng = Mt19937MersenneTwisterRng::new_seed(seed);
-        if rng.extract_number() == random {
-            return seed;
-        }
-    }
-    panic!("Seed not found!");
+    Mt19937SeedCracker::new()
+        .first_output(random)
+        .find(now - 60 * 60..=now)
+        .expect("Seed not found!")
 }
 #[cfg(test)]
 mod test {
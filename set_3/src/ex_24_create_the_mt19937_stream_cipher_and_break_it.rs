@@ -18,85 +18,40 @@
 //! Write a function to check if any given password token is actually the product of an MT19937
 //! PRNG seeded with the current time.
 
-use crypto::random::{Mt19937MersenneTwisterRng, Rng};
+use crypto::random::{
+    mt19937_break, mt19937_decrypt, mt19937_encrypt, mt19937_password_reset_token,
+    Mt19937TokenDetector, TOKEN_SIZE,
+};
 
-struct MT19937Iterator {
-    rng: Mt19937MersenneTwisterRng,
-    key: u16,
-}
-impl MT19937Iterator {
-    fn new(key: u16) -> Self {
-        Self {
-            rng: Mt19937MersenneTwisterRng::new_seed(key as u32),
-            key,
-        }
-    }
-}
-impl Iterator for MT19937Iterator {
-    type Item = u8;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        Some((self.rng.rand() * 100.0) as u32 as u8)
-    }
-}
+/// The cipher lives in `crypto::random` now, with 32-bit keys.
 fn encrypt(pt: &[u8], key: u16) -> Vec<u8> {
-    pt.into_iter()
-        .zip(MT19937Iterator::new(key))
-        .map(|(a, b)| a ^ b)
-        .collect()
+    mt19937_encrypt(pt, key as u32)
 }
 fn decrypt(ct: &[u8], key: u16) -> Vec<u8> {
-    encrypt(ct, key)
+    mt19937_decrypt(ct, key as u32)
 }
 
 ///  Use your function to encrypt a known plaintext (say, 14 consecutive 'A' characters)
 /// prefixed by a random number of random characters.
 /// From the ciphertext, recover the "key" (the 16 bit seed).
-/// Because 16 bit is super small, let's just bruteforce it
+/// Because 16 bit is super small, let's just bruteforce it: the known plaintext gives the end of
+/// the keystream.
 fn break_mt19937(ct: &[u8]) -> (Vec<u8>, u16) {
-    for i in 0..=0xff {
-        let pt = decrypt(ct, i);
-        if pt.ends_with(b"aaaaaaaaaaaaaa") {
-            println!("{:?}", String::from_utf8_lossy(&pt));
-            return (pt, i);
-        }
-    }
-    unreachable!();
+    const KNOWN_PT: &[u8] = b"aaaaaaaaaaaaaa";
+    let key = mt19937_break(ct, KNOWN_PT, 0..=0xffff).expect("Key not found") as u16;
+    (decrypt(ct, key), key)
 }
 
-// 0xf it's an hack to keep tests fast
-// the script requested to use 0xffff.
-const MAX_SEED_SIZE: u16 = 0xf;
-
 ///  Use the same idea to generate a random "password reset token" using MT19937 seeded from
 /// the current time.
-fn generate_password_reset_token() -> [u8; 16] {
-    let time_seed = (chrono::Utc::now().timestamp() as u16) % MAX_SEED_SIZE;
-    let token: Vec<u8> = MT19937Iterator::new(time_seed)
-        .into_iter()
-        .take(16)
-        .collect();
-    let mut ret = [0u8; 16];
-    ret.copy_from_slice(&token);
-    ret
+fn generate_password_reset_token() -> [u8; TOKEN_SIZE] {
+    mt19937_password_reset_token()
 }
 
 ///  Write a function to check if any given password token is actually the product of an
 /// MT19937 PRNG seeded with the current time.
 fn is_generated_using_mt19937(token: &[u8]) -> bool {
-    for i in 0..=MAX_SEED_SIZE {
-        // 0xf is an hack to keep tests fast
-        let identical = MT19937Iterator::new(i)
-            .into_iter()
-            .take(16)
-            .zip(token)
-            .filter(|(a, b)| a != *b)
-            .count();
-        if identical == 0 {
-            return true;
-        }
-    }
-    false
+    Mt19937TokenDetector::new().detect(token).is_some()
 }
 
 #[cfg(test)]
@@ -104,6 +59,10 @@ mod test {
     use crate::ex_24_create_the_mt19937_stream_cipher_and_break_it::{
         break_mt19937, decrypt, encrypt, generate_password_reset_token, is_generated_using_mt19937,
     };
+    use crypto::random::{
+        bits_from_bytes, run_battery, Mt19937Keystream, Mt19937MersenneTwisterRng, Rng as _,
+        TestResult,
+    };
     use rand::distributions::Alphanumeric;
     use rand::Rng;
 
@@ -117,7 +76,7 @@ mod test {
 
     #[test]
     fn test_break() {
-        let key = 0x10;
+        let key = 0xfedc;
         let known_pt = b"aaaaaaaaaaaaaa";
 
         let pt: String = rand::thread_rng()
@@ -135,4 +94,18 @@ mod test {
         assert!(is_generated_using_mt19937(&token));
         assert!(!is_generated_using_mt19937(b"aaaaaaaaaaaaaa"));
     }
+
+    #[test]
+    fn test_keystream_randomness() {
+        let keystream = bits_from_bytes(Mt19937Keystream::new(0xfedc).take(12_500));
+        let results = run_battery(&keystream);
+        assert!(results.iter().all(TestResult::passed), "{:?}", results);
+
+        // The first version used `(rand() * 100.0) as u8`: bytes below 100, the top bit is
+        // always 0.
+        let mut rng = Mt19937MersenneTwisterRng::new_seed(0xfedc);
+        let biased = (0..12_500).map(|_| (rng.rand() * 100.0) as u8);
+        let results = run_battery(&bits_from_bytes(biased));
+        assert!(!results.iter().all(TestResult::passed), "{:?}", results);
+    }
 }
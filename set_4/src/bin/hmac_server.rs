@@ -0,0 +1,24 @@
+//! A local HMAC verification server, a target for timing attacks. See `set_4::hmac_server`.
+
+use set_4::hmac_server::{run, ServerConfig, USAGE};
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let config = match ServerConfig::from_args(args) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        }
+    };
+    println!(
+        "Listening on 127.0.0.1:{} with {:?}",
+        config.port, config.comparison
+    );
+    run(config).await
+}
@@ -56,27 +56,52 @@ fn solve(
     original_message_digest: Vec<u8>,
     validate: impl Fn(&[u8], &[u8]) -> bool,
 ) -> Vec<u8> {
-    let sha1state = Sha1State::from_message_digset(&original_message_digest);
+    forge(
+        message,
+        target,
+        &original_message_digest,
+        glue_padding,
+        Sha1State::from_message_digset,
+        |state, payload_len| sha1_state_len(state, target, payload_len).0,
+        validate,
+    )
+    .expect("No solution found")
+}
+
+/// The length extension attack, generic over the hash so it can be pointed at other constructions.
+/// `extend` hashes `target` starting from the state recovered by `from_digest`.
+fn forge<S: Clone>(
+    message: &[u8],
+    target: &[u8],
+    original_message_digest: &[u8],
+    glue_padding: impl Fn(&[u8], usize) -> Vec<u8>,
+    from_digest: impl Fn(&[u8]) -> S,
+    extend: impl Fn(S, usize) -> Vec<u8>,
+    validate: impl Fn(&[u8], &[u8]) -> bool,
+) -> Option<Vec<u8>> {
+    let state = from_digest(original_message_digest);
     for guessed_key_len in 16..=16 {
         // original-message | glue_padding
         let mut forged_message = glue_padding(message, guessed_key_len);
         // original-message || glue-padding || new-message
         forged_message.extend(target);
         let payload_len = guessed_key_len + forged_message.len();
-        let forged_message_digest = sha1_state_len(sha1state.clone(), target, payload_len).0;
-        // validate SHA1(key || original-message || glue-padding || new-message)
+        let forged_message_digest = extend(state.clone(), payload_len);
+        // validate HASH(key || original-message || glue-padding || new-message)
         if validate(&forged_message, &forged_message_digest) {
-            return forged_message_digest;
+            return Some(forged_message_digest);
         }
     }
-    panic!("No solution found");
+    None
 }
 
 #[cfg(test)]
 mod test {
-    use crate::ex_29_break_sha1_keyed_mac_length_extension::solve;
+    use crate::ex_29_break_sha1_keyed_mac_length_extension::{forge, solve};
     use crypto::aes::random_key;
+    use crypto::hash::sha3::{sha3_256, sha3_256_state_len, sha3_padding_needed, Sha3State};
     use crypto::mac::sha1_mac;
+    use std::iter;
 
     #[test]
     fn test_solve() {
@@ -95,4 +120,38 @@ mod test {
             },
         );
     }
+
+    /// SHA-3 is a sponge: the digest doesn't contain the full internal state, so the same attack
+    /// can't resume hashing from it.
+    #[test]
+    fn test_forge_fails_against_sha3() {
+        const MESSAGE: &[u8] =
+            b"comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon";
+        const TARGET: &[u8] = b";admin=true";
+        const RATE: usize = 136;
+        let key = random_key();
+        let sha3_mac = |message: &[u8]| {
+            let mut buf = key.to_vec();
+            buf.extend_from_slice(message);
+            sha3_256(&buf)
+        };
+        let glue_padding = |message: &[u8], guessed_key_len: usize| {
+            let padding = sha3_padding_needed(guessed_key_len + message.len(), RATE);
+            let mut forged_message = message.to_vec();
+            forged_message.push(0x06);
+            forged_message.extend(iter::repeat_n(0, padding - 1));
+            *forged_message.last_mut().unwrap() |= 0x80;
+            forged_message
+        };
+        let forged = forge(
+            MESSAGE,
+            TARGET,
+            &sha3_mac(MESSAGE),
+            glue_padding,
+            Sha3State::from_message_digest,
+            |state, payload_len| sha3_256_state_len(state, TARGET, payload_len).0,
+            |forged_message, forged_digest: &[u8]| sha3_mac(forged_message) == forged_digest,
+        );
+        assert!(forged.is_none());
+    }
 }
@@ -7,150 +7,92 @@
 //! the same signature, take the average, and then pick the byte which took the highest time as
 //! the next byte for the result.
 //!
-//! In this code I'm not averaging multiple requests for brevity, but the code is able to work up to
-//! 1 milliseconds of wait time.
-
-use actix_web::{get, web, App, HttpResponse, HttpServer, ResponseError};
-use crypto::hash::from_hex;
-use crypto::mac::hmac_sha1;
-use std::fmt::{Display, Formatter};
-use std::future::Future;
-use std::time::Instant;
-use std::{thread, time};
-
-const FILE: &[u8] =
-    b"Early-exit string compares are probably the most common source of cryptographic \
-timing leaks, but they aren't especially easy to exploit. In fact, many timing leaks (for instance\
-, any in C, C++, Ruby, or Python) probably aren't exploitable over a wide-area network at all. \
-To play with attacking real-world timing leaks, you have to start writing low-level timing code. \
-We're keeping things cryptographic in these challenges. ";
-
-const HMAC_KEY: &[u8] = b"some-secret-key";
-
-const LIMIT_FOR_TESTING: usize = 3;
-const TIMEOUT_NS: usize = 2_000_000;
-
-#[get("/test")]
-async fn verify_signature(
-    query: web::Query<SignatureQuery>,
-) -> Result<HttpResponse, SignatureError> {
-    let mut mac = hmac_sha1(HMAC_KEY, &FILE);
-
-    // REMOVE ME: just for faster testing
-    mac.truncate(LIMIT_FOR_TESTING);
-    // -----
-
-    let expected_signature = &mac;
-    if insecure_compare(&from_hex(&query.signature).unwrap(), expected_signature) {
-        Ok(HttpResponse::Ok().body("OK"))
-    } else {
-        Err(SignatureError::InvalidSignature)
-    }
-}
-
-fn insecure_compare(a: &[u8], b: &[u8]) -> bool {
-    for (byte_a, byte_b) in a.into_iter().zip(b.iter()) {
-        if *byte_a != *byte_b {
-            return false;
-        }
-        thread::sleep(time::Duration::from_nanos(TIMEOUT_NS as u64));
-    }
-    a.len() == b.len()
-}
-
-#[derive(serde::Deserialize)]
-struct SignatureQuery {
-    file: String,
-    signature: String,
-}
-
-#[derive(Debug)]
-enum SignatureError {
-    InvalidSignature,
-}
-
-impl Display for SignatureError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Invalid signature")
-    }
-}
-
-impl ResponseError for SignatureError {
-    fn status_code(&self) -> actix_web::http::StatusCode {
-        actix_web::http::StatusCode::INTERNAL_SERVER_ERROR
-    }
-}
-
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    HttpServer::new(|| App::new().service(verify_signature))
-        .bind("127.0.0.1:9000")?
-        .run()
-        .await
-}
-use std::pin::Pin;
-
-async fn find_hmac<F>(sender: F) -> Vec<u8>
-where
-    F: Fn(Vec<u8>) -> Pin<Box<dyn Future<Output = bool>>>,
-{
-    let mut mac = hmac_sha1(HMAC_KEY, &FILE);
-    mac.truncate(LIMIT_FOR_TESTING);
-
-    let mut current_hmac = Vec::new();
-    loop {
-        let mut requests = vec![];
-        for i in 0..=255u8 {
-            let mut buf = current_hmac.clone();
-            buf.push(i);
-            let start = Instant::now();
-            // Call your function here
-            let ret = sender(buf.clone()).await;
-            if ret {
-                if mac.len() != buf.len() {
-                    panic!("Something weird happened");
-                }
-                return buf;
-            }
-            let duration = start.elapsed();
-            requests.push((duration.as_nanos(), i));
-        }
-        let found = requests.iter().max_by(|a, b| a.0.cmp(&b.0)).unwrap();
-        current_hmac.push(found.1);
-        if current_hmac.len() == mac.len() {
-            return current_hmac;
-        }
+//! The attack lives in `timing_attack`: it samples each candidate several times, compares the
+//! medians, and keeps sampling the leading candidates until the best one is significantly slower.
+
+use crate::hmac_server::{Comparison, ServerConfig};
+use std::time::Duration;
+
+/// The sleep after each matching byte of `insecure_compare`.
+pub const DELAY: Duration = Duration::from_millis(5);
+
+/// The server of the challenge: `hmac_server::verify_signature` with HMAC-SHA1 and an early-exit
+/// comparison sleeping `DELAY` per byte.
+pub fn server_config(key: &[u8]) -> ServerConfig {
+    ServerConfig {
+        key: key.to_vec(),
+        comparison: Comparison::EarlyExit(DELAY),
+        ..ServerConfig::default()
     }
 }
 
 #[cfg(test)]
 mod tests {
-
-    use crate::ex_32_break_hmac_sha1_with_a_slightly_less_artificial_timing_leak::{
-        find_hmac, verify_signature, FILE, HMAC_KEY, LIMIT_FOR_TESTING,
-    };
-    use actix_web::http::header::ContentType;
-    use actix_web::{test, App};
+    use crate::ex_32_break_hmac_sha1_with_a_slightly_less_artificial_timing_leak::server_config;
+    use crate::hmac_server::{verify_signature, Comparison};
+    use crate::timing_attack::simulated::{LeakModel, SimulatedOracle};
+    use crate::timing_attack::TimingAttack;
+    use actix_web::{test, web, App};
     use crypto::hash::to_hex;
     use crypto::mac::hmac_sha1;
+    use std::time::Duration;
+
+    const KEY: &[u8] = b"some-secret-key";
+    const FILE: &[u8] = b"foo";
 
     #[actix_web::test]
     async fn test_index_get() {
-        let mut mac = hmac_sha1(HMAC_KEY, &FILE);
-        mac.truncate(LIMIT_FOR_TESTING);
-
-        let ret = find_hmac(move |buf| {
-            Box::pin(async move {
-                let app = test::init_service(App::new().service(verify_signature)).await;
-                let req = test::TestRequest::default()
-                    .uri(&format!("/test?file=foo&signature={}", to_hex(buf)))
-                    .insert_header(ContentType::plaintext())
-                    .to_request();
-                test::call_service(&app, req).await.status().is_success()
-            })
-        })
+        let mac = hmac_sha1(KEY, FILE);
+        let mut wrong = mac.clone();
+        wrong[19] ^= 1;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(server_config(KEY)))
+                .service(verify_signature),
+        )
         .await;
+        for (signature, expected) in [(&mac, true), (&wrong, false)] {
+            let req = test::TestRequest::get()
+                .uri(&format!("/test?file=foo&signature={}", to_hex(signature)))
+                .to_request();
+            let status = test::call_service(&app, req).await.status();
+            assert_eq!(status.is_success(), expected);
+        }
+    }
 
-        assert_eq!(ret, mac);
+    /// The leak of the challenge server, with network jitter of the same order and hiccups: the
+    /// test fails if the server stops leaking or the delays drift apart.
+    fn leak_model() -> LeakModel {
+        let per_byte = match server_config(KEY).comparison {
+            Comparison::EarlyExit(delay) => delay,
+            comparison => panic!("{:?} doesn't leak", comparison),
+        };
+        LeakModel {
+            base: Duration::from_millis(1),
+            per_byte,
+            jitter: per_byte / 2,
+            outlier_probability: 0.05,
+            outlier_delay: per_byte * 10,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_attack() {
+        let config = server_config(KEY);
+        let mac = config.mac.compute(&config.key, FILE);
+
+        // The solution of ex_31, a single sample of each candidate, breaks.
+        let oracle = SimulatedOracle::new(&mac, leak_model(), 32);
+        let mut attack = TimingAttack::new(mac.len());
+        attack.min_samples = 1;
+        attack.max_samples = 1;
+        attack.max_backtracks = 0;
+        assert_eq!(attack.run(&oracle).await, None);
+
+        // More samples and backtracking break it again.
+        let oracle = SimulatedOracle::new(&mac, leak_model(), 32);
+        let mut attack = TimingAttack::new(mac.len());
+        assert_eq!(attack.run(&oracle).await, Some(mac));
+        println!("Simulated time: {:?}", oracle.elapsed());
     }
 }
@@ -0,0 +1,251 @@
+//! A configurable version of the ex_31 and ex_32 servers, to have local targets for timing
+//! attacks. It serves `/test?file=...&signature=...`, returning a 200 if the signature is the MAC
+//! of the file and a 500 otherwise.
+//!
+//! Run it with `cargo run --bin hmac_server -- [options]`, see `USAGE`.
+
+use actix_web::{get, web, App, HttpResponse, HttpServer, ResponseError};
+use crypto::hash::from_hex;
+use crypto::mac::{
+    constant_time_eq, hmac_sha1, Blake2bMac, Blake2sMac, HmacSha1, Mac, Md4Mac, Sha1Mac,
+};
+use std::fmt::{Display, Formatter};
+use std::thread;
+use std::time::Duration;
+
+pub const USAGE: &str = "Usage: hmac_server [options]
+    --key <key>            the MAC key (default: a random one)
+    --mac <algorithm>      hmac-sha1 (default), sha1, md4, blake2b or blake2s
+    --compare <strategy>   early-exit (default), constant-time or double-hmac
+    --delay <ms>           sleep after each matching byte with early-exit (default: 50)
+    --port <port>          (default: 9000)";
+
+/// How the server compares the signature with the expected MAC.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    /// Byte by byte, sleeping after each matching byte and returning at the first difference:
+    /// the `insecure_compare` of ex_31.
+    EarlyExit(Duration),
+    /// `constant_time_eq`
+    ConstantTime,
+    /// Compares HMACs of both values under a random key: even an early-exit comparison then
+    /// leaks nothing useful, since the attacker can't control the bytes being compared.
+    DoubleHmac,
+}
+
+impl Comparison {
+    pub fn compare(&self, a: &[u8], b: &[u8]) -> bool {
+        match self {
+            Comparison::EarlyExit(delay) => {
+                for (byte_a, byte_b) in a.iter().zip(b) {
+                    if byte_a != byte_b {
+                        return false;
+                    }
+                    thread::sleep(*delay);
+                }
+                a.len() == b.len()
+            }
+            Comparison::ConstantTime => constant_time_eq(a, b),
+            Comparison::DoubleHmac => {
+                let key: [u8; 16] = rand::random();
+                hmac_sha1(&key, a) == hmac_sha1(&key, b)
+            }
+        }
+    }
+}
+
+pub struct ServerConfig {
+    pub key: Vec<u8>,
+    pub mac: Box<dyn Mac + Send + Sync>,
+    pub comparison: Comparison,
+    pub port: u16,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            key: rand::random::<[u8; 16]>().to_vec(),
+            mac: Box::new(HmacSha1),
+            comparison: Comparison::EarlyExit(Duration::from_millis(50)),
+            port: 9000,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Parses the command line options, without the program name.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut config = Self::default();
+        let mut compare = "early-exit".to_string();
+        let mut delay = Duration::from_millis(50);
+        let mut args = args.into_iter();
+        while let Some(option) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", option))?;
+            match option.as_str() {
+                "--key" => config.key = value.into_bytes(),
+                "--mac" => {
+                    config.mac = match value.as_str() {
+                        "hmac-sha1" => Box::new(HmacSha1),
+                        "sha1" => Box::new(Sha1Mac),
+                        "md4" => Box::new(Md4Mac),
+                        "blake2b" => Box::new(Blake2bMac),
+                        "blake2s" => Box::new(Blake2sMac),
+                        _ => return Err(format!("Unknown MAC: {}", value)),
+                    }
+                }
+                "--compare" => compare = value,
+                "--delay" => {
+                    let ms: f64 = value
+                        .parse()
+                        .map_err(|_| format!("Invalid delay: {}", value))?;
+                    delay = Duration::try_from_secs_f64(ms / 1000.0)
+                        .map_err(|_| format!("Invalid delay: {}", value))?;
+                }
+                "--port" => {
+                    config.port = value
+                        .parse()
+                        .map_err(|_| format!("Invalid port: {}", value))?
+                }
+                _ => return Err(format!("Unknown option: {}", option)),
+            }
+        }
+        config.comparison = match compare.as_str() {
+            "early-exit" => Comparison::EarlyExit(delay),
+            "constant-time" => Comparison::ConstantTime,
+            "double-hmac" => Comparison::DoubleHmac,
+            _ => return Err(format!("Unknown comparison: {}", compare)),
+        };
+        Ok(config)
+    }
+}
+
+#[get("/test")]
+pub async fn verify_signature(
+    config: web::Data<ServerConfig>,
+    query: web::Query<SignatureQuery>,
+) -> Result<HttpResponse, SignatureError> {
+    let mac = config.mac.compute(&config.key, query.file.as_bytes());
+    let signature = from_hex(&query.signature).ok_or(SignatureError::InvalidSignature)?;
+    if config.comparison.compare(&signature, &mac) {
+        Ok(HttpResponse::Ok().body("OK"))
+    } else {
+        Err(SignatureError::InvalidSignature)
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct SignatureQuery {
+    file: String,
+    signature: String,
+}
+
+#[derive(Debug)]
+pub enum SignatureError {
+    InvalidSignature,
+}
+
+impl Display for SignatureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid signature")
+    }
+}
+
+impl ResponseError for SignatureError {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        actix_web::http::StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+pub async fn run(config: ServerConfig) -> std::io::Result<()> {
+    let port = config.port;
+    let config = web::Data::new(config);
+    HttpServer::new(move || {
+        App::new()
+            .app_data(config.clone())
+            .service(verify_signature)
+    })
+    .bind(("127.0.0.1", port))?
+    .run()
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hmac_server::{verify_signature, Comparison, ServerConfig};
+    use actix_web::{web, App};
+    use crypto::hash::to_hex;
+    use crypto::mac::{md4_mac, Md4Mac};
+    use std::time::Duration;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_from_args() {
+        let config = ServerConfig::from_args(args(
+            "--key secret --mac md4 --compare early-exit --delay 0.5 --port 8080",
+        ))
+        .unwrap();
+        assert_eq!(config.key, b"secret");
+        assert_eq!(config.mac.compute(b"k", b"m"), md4_mac(b"k", b"m"));
+        assert_eq!(
+            config.comparison,
+            Comparison::EarlyExit(Duration::from_micros(500))
+        );
+        assert_eq!(config.port, 8080);
+
+        let config = ServerConfig::from_args(args("--compare double-hmac")).unwrap();
+        assert_eq!(config.comparison, Comparison::DoubleHmac);
+        assert_eq!(config.port, 9000);
+
+        assert!(ServerConfig::from_args(args("--mac md5")).is_err());
+        assert!(ServerConfig::from_args(args("--port")).is_err());
+        for delay in ["-5", "nan", "inf", "1e400"] {
+            assert_eq!(
+                ServerConfig::from_args(args(&format!("--delay {}", delay))).err(),
+                Some(format!("Invalid delay: {}", delay))
+            );
+        }
+        assert!(ServerConfig::from_args(args("--verbose yes")).is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_verify_signature() {
+        for comparison in [
+            Comparison::EarlyExit(Duration::ZERO),
+            Comparison::ConstantTime,
+            Comparison::DoubleHmac,
+        ] {
+            let config = ServerConfig {
+                key: b"secret".to_vec(),
+                mac: Box::new(Md4Mac),
+                comparison,
+                port: 9000,
+            };
+            let app = actix_web::test::init_service(
+                App::new()
+                    .app_data(web::Data::new(config))
+                    .service(verify_signature),
+            )
+            .await;
+            let mac = md4_mac(b"secret", b"foo");
+            let mut wrong = mac.clone();
+            wrong[15] ^= 1;
+            for (signature, expected) in [
+                (to_hex(&mac), true),
+                (to_hex(&wrong), false),
+                (to_hex(&mac[..15]), false),
+                ("zz".to_string(), false),
+            ] {
+                let req = actix_web::test::TestRequest::get()
+                    .uri(&format!("/test?file=foo&signature={}", signature))
+                    .to_request();
+                let status = actix_web::test::call_service(&app, req).await.status();
+                assert_eq!(status.is_success(), expected, "{:?}", comparison);
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! A timing attack on early-exit MAC comparisons, shared by ex_31 and ex_32.
+//!
+//! The MAC is recovered one byte at a time: for each candidate byte we submit the known prefix,
+//! the candidate, and zeros up to the MAC length, and the candidate that makes the server take
+//! the longest is the right one, since the comparison went one byte further.
+//!
+//! Timings are noisy, so each candidate is sampled a few times and compared with a robust
+//! statistic. The sampling is adaptive: more samples are only taken for the leading candidates,
+//! until Welch's t-test says the best one is significantly slower than the runner-up. If no
+//! candidate stands out, the previous byte was likely wrong (all the candidates stop at the same
+//! place) and the attack backtracks.
+
+pub mod simulated;
+pub mod stats;
+
+use stats::{median, trim, trimmed_mean, welch_t};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// The outcome of submitting one candidate MAC.
+#[derive(Debug, Clone, Copy)]
+pub struct Measurement {
+    pub accepted: bool,
+    pub duration: Duration,
+}
+
+/// Something that checks a MAC and tells how long it took, e.g. an HTTP endpoint.
+pub trait TimingOracle {
+    fn measure(&self, candidate: &[u8]) -> impl Future<Output = Measurement>;
+}
+
+/// A `TimingOracle` measuring an async check with the wall clock.
+pub struct WallClockOracle<F>(pub F);
+
+impl<F, Fut> TimingOracle for WallClockOracle<F>
+where
+    F: Fn(Vec<u8>) -> Fut,
+    Fut: Future<Output = bool>,
+{
+    async fn measure(&self, candidate: &[u8]) -> Measurement {
+        let start = Instant::now();
+        let accepted = (self.0)(candidate.to_vec()).await;
+        Measurement {
+            accepted,
+            duration: start.elapsed(),
+        }
+    }
+}
+
+/// How to summarize the samples of a candidate.
+#[derive(Debug, Clone, Copy)]
+pub enum Statistic {
+    Median,
+    /// The mean without the given fraction of the smallest and of the largest samples.
+    TrimmedMean(f64),
+}
+
+impl Statistic {
+    fn apply(&self, samples: &[f64]) -> f64 {
+        match self {
+            Statistic::Median => median(samples),
+            Statistic::TrimmedMean(fraction) => trimmed_mean(samples, *fraction),
+        }
+    }
+}
+
+/// Reported after each decision of the attack.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    /// The bytes recovered so far.
+    pub prefix: Vec<u8>,
+    /// The t statistic of the last byte against the runner-up.
+    pub confidence: f64,
+    /// Whether the last byte was dropped instead of added.
+    pub backtracked: bool,
+    /// Number of requests so far.
+    pub queries: u64,
+}
+
+type ProgressCallback = Box<dyn FnMut(&Progress)>;
+
+pub struct TimingAttack {
+    pub mac_length: usize,
+    pub statistic: Statistic,
+    /// Samples of every candidate before comparing them.
+    pub min_samples: usize,
+    /// Samples of a candidate after which we stop and take the best one.
+    pub max_samples: usize,
+    /// How many of the leading candidates get more samples.
+    pub contenders: usize,
+    /// The t statistic to accept a byte.
+    pub confidence: f64,
+    pub max_backtracks: usize,
+    /// Fraction of outliers dropped on each side before the t-test.
+    pub trim: f64,
+    progress: Option<ProgressCallback>,
+}
+
+impl TimingAttack {
+    pub fn new(mac_length: usize) -> Self {
+        Self {
+            mac_length,
+            statistic: Statistic::Median,
+            min_samples: 3,
+            max_samples: 50,
+            contenders: 8,
+            confidence: 4.0,
+            max_backtracks: 10,
+            trim: 0.1,
+            progress: None,
+        }
+    }
+
+    pub fn on_progress(mut self, progress: impl FnMut(&Progress) + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Recovers the MAC the oracle accepts, or `None` if it ran out of backtracks or
+    /// `mac_length` is 0.
+    pub async fn run(&mut self, oracle: &impl TimingOracle) -> Option<Vec<u8>> {
+        if self.mac_length == 0 {
+            return None;
+        }
+        let mut prefix = vec![];
+        let mut backtracks = 0;
+        let mut queries = 0;
+        loop {
+            let position = prefix.len();
+            // Each backtrack means the noise fooled us: take more samples from then on.
+            let effort = 1 + backtracks;
+            let (byte, confidence, accepted) =
+                self.find_byte(oracle, &prefix, effort, &mut queries).await;
+            if accepted {
+                prefix.push(byte);
+                self.report(&prefix, confidence, false, queries);
+                return Some(prefix);
+            }
+            let last = position == self.mac_length - 1;
+            if confidence >= self.confidence && !last {
+                prefix.push(byte);
+                self.report(&prefix, confidence, false, queries);
+            } else if backtracks < self.max_backtracks && position > 0 {
+                // Measure the previous byte again: if it was right we'll find it again, and
+                // come back here with more samples.
+                backtracks += 1;
+                prefix.pop();
+                self.report(&prefix, confidence, true, queries);
+            } else if last {
+                return None;
+            } else {
+                // Out of backtracks, go on with our best guess.
+                prefix.push(byte);
+                self.report(&prefix, confidence, false, queries);
+            }
+        }
+    }
+
+    fn report(&mut self, prefix: &[u8], confidence: f64, backtracked: bool, queries: u64) {
+        if let Some(progress) = &mut self.progress {
+            progress(&Progress {
+                prefix: prefix.to_vec(),
+                confidence,
+                backtracked,
+                queries,
+            });
+        }
+    }
+
+    /// Finds the slowest candidate for the byte after `prefix`.
+    /// Returns it, its t statistic against the runner-up, and whether the oracle accepted it.
+    async fn find_byte(
+        &self,
+        oracle: &impl TimingOracle,
+        prefix: &[u8],
+        effort: usize,
+        queries: &mut u64,
+    ) -> (u8, f64, bool) {
+        let candidates: Vec<u8> = (0..=255).collect();
+        let mut samples: Vec<Vec<f64>> = vec![vec![]; 256];
+        let mut to_sample = candidates.clone();
+        for round in 0.. {
+            let rounds = if round == 0 {
+                self.min_samples * effort
+            } else {
+                1
+            };
+            for _ in 0..rounds {
+                // Interleave the candidates, so a slow period affects all of them.
+                for &byte in &to_sample {
+                    let mut candidate = prefix.to_vec();
+                    candidate.push(byte);
+                    candidate.resize(self.mac_length, 0);
+                    let measurement = oracle.measure(&candidate).await;
+                    *queries += 1;
+                    if measurement.accepted {
+                        return (byte, f64::INFINITY, true);
+                    }
+                    samples[byte as usize].push(measurement.duration.as_nanos() as f64);
+                }
+            }
+
+            let mut ranking: Vec<(f64, u8)> = candidates
+                .iter()
+                .map(|&byte| (self.statistic.apply(&samples[byte as usize]), byte))
+                .collect();
+            ranking.sort_by(|a, b| b.0.total_cmp(&a.0));
+            let best = ranking[0].1;
+            let best_samples = &samples[best as usize];
+            let runner_up = &samples[ranking[1].1 as usize];
+            let t = if best_samples.len() < 2 || runner_up.len() < 2 {
+                0.0
+            } else {
+                welch_t(&trim(best_samples, self.trim), &trim(runner_up, self.trim))
+            };
+            if t >= self.confidence || best_samples.len() >= self.max_samples * effort {
+                return (best, t, false);
+            }
+            to_sample = ranking
+                .iter()
+                .take(self.contenders)
+                .map(|(_, byte)| *byte)
+                .collect();
+        }
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::timing_attack::simulated::{LeakModel, SimulatedOracle};
+    use crate::timing_attack::{TimingAttack, WallClockOracle};
+    use actix_web::{get, test, web, App, HttpResponse};
+    use crypto::hash::{from_hex, to_hex};
+    use crypto::mac::hmac_sha1;
+    use std::time::{Duration, Instant};
+
+    const KEY: &[u8] = b"some-secret-key";
+    const FILE: &[u8] = b"foo";
+
+    #[get("/test")]
+    async fn verify(query: web::Query<Vec<(String, String)>>) -> HttpResponse {
+        let mac = hmac_sha1(KEY, FILE);
+        let signature = from_hex(&query[0].1).unwrap();
+        for (a, b) in signature.iter().zip(&mac) {
+            if a != b {
+                return HttpResponse::InternalServerError().finish();
+            }
+            // Busy wait, sleeping is much less precise.
+            let start = Instant::now();
+            while start.elapsed() < Duration::from_micros(100) {}
+        }
+        HttpResponse::Ok().finish()
+    }
+
+    // Measures real requests: about 15 seconds, the simulated oracle covers the noisier leaks.
+    #[actix_web::test]
+    async fn test_full_hmac() {
+        let app = test::init_service(App::new().service(verify)).await;
+        let app = &app;
+        let oracle = WallClockOracle(move |candidate: Vec<u8>| async move {
+            let req = test::TestRequest::get()
+                .uri(&format!("/test?signature={}", to_hex(candidate)))
+                .to_request();
+            test::call_service(app, req).await.status().is_success()
+        });
+        let mut attack = TimingAttack::new(20).on_progress(|progress| {
+            println!(
+                "{} t={:.1} backtracked={} queries={}",
+                to_hex(&progress.prefix),
+                progress.confidence,
+                progress.backtracked,
+                progress.queries
+            )
+        });
+        assert_eq!(attack.run(&oracle).await, Some(hmac_sha1(KEY, FILE)));
+    }
+
+    #[actix_web::test]
+    async fn test_empty_mac() {
+        let oracle = SimulatedOracle::new(b"", LeakModel::exact(Duration::from_millis(1)), 0);
+        assert_eq!(TimingAttack::new(0).run(&oracle).await, None);
+        assert_eq!(oracle.elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,151 @@
+//! A simulated timing oracle: an early-exit comparison on a virtual clock, so the attacks run in
+//! milliseconds and always see the same noise for the same seed.
+
+use crate::timing_attack::{Measurement, TimingOracle};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::{Cell, RefCell};
+use std::f64::consts::PI;
+use std::time::Duration;
+
+/// A clock that only moves when told to.
+#[derive(Debug, Default)]
+pub struct VirtualClock {
+    now: Cell<Duration>,
+}
+
+impl VirtualClock {
+    pub fn now(&self) -> Duration {
+        self.now.get()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+/// How long a simulated request takes.
+#[derive(Debug, Clone, Copy)]
+pub struct LeakModel {
+    /// The time of a request failing at the first byte.
+    pub base: Duration,
+    /// Added for each matching byte: the leak.
+    pub per_byte: Duration,
+    /// Standard deviation of the Gaussian noise added to each request.
+    pub jitter: Duration,
+    /// Probability of a request hitting a hiccup (garbage collection, scheduling, ...).
+    pub outlier_probability: f64,
+    /// The extra time of a hiccup.
+    pub outlier_delay: Duration,
+}
+
+impl LeakModel {
+    /// A clean leak without noise, like ex_31.
+    pub fn exact(per_byte: Duration) -> Self {
+        Self {
+            base: Duration::from_micros(500),
+            per_byte,
+            jitter: Duration::ZERO,
+            outlier_probability: 0.0,
+            outlier_delay: Duration::ZERO,
+        }
+    }
+}
+
+/// Checks candidates against `mac` with an early-exit comparison, taking the time the
+/// `LeakModel` says on a `VirtualClock`.
+pub struct SimulatedOracle {
+    mac: Vec<u8>,
+    model: LeakModel,
+    clock: VirtualClock,
+    rng: RefCell<StdRng>,
+}
+
+impl SimulatedOracle {
+    pub fn new(mac: &[u8], model: LeakModel, seed: u64) -> Self {
+        Self {
+            mac: mac.to_vec(),
+            model,
+            clock: VirtualClock::default(),
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// The simulated time spent by all the requests so far.
+    pub fn elapsed(&self) -> Duration {
+        self.clock.now()
+    }
+
+    /// A Gaussian sample with the Box–Muller transform.
+    fn gaussian(&self, standard_deviation: f64) -> f64 {
+        let mut rng = self.rng.borrow_mut();
+        let u1: f64 = 1.0 - rng.gen::<f64>();
+        let u2: f64 = rng.gen();
+        standard_deviation * (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+
+    fn request_time(&self, matching: usize) -> Duration {
+        let model = &self.model;
+        let mut nanos = (model.base + model.per_byte * matching as u32).as_nanos() as f64;
+        nanos += self.gaussian(model.jitter.as_nanos() as f64);
+        if self.rng.borrow_mut().gen_bool(model.outlier_probability) {
+            nanos += model.outlier_delay.as_nanos() as f64;
+        }
+        Duration::from_nanos(nanos.max(0.0) as u64)
+    }
+}
+
+impl TimingOracle for SimulatedOracle {
+    async fn measure(&self, candidate: &[u8]) -> Measurement {
+        let start = self.clock.now();
+        let matching = candidate
+            .iter()
+            .zip(&self.mac)
+            .take_while(|(a, b)| a == b)
+            .count();
+        self.clock.advance(self.request_time(matching));
+        Measurement {
+            accepted: candidate == self.mac.as_slice(),
+            duration: self.clock.now() - start,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::timing_attack::simulated::{LeakModel, SimulatedOracle};
+    use crate::timing_attack::{Statistic, TimingAttack};
+    use crypto::mac::hmac_sha1;
+    use std::time::Duration;
+
+    const KEY: &[u8] = b"some-secret-key";
+
+    #[actix_web::test]
+    async fn test_exact_leak() {
+        let mac = hmac_sha1(KEY, b"foo");
+        let oracle = SimulatedOracle::new(&mac, LeakModel::exact(Duration::from_millis(50)), 0);
+        let mut attack = TimingAttack::new(20);
+        attack.min_samples = 1;
+        assert_eq!(attack.run(&oracle).await, Some(mac));
+        println!("Simulated time: {:?}", oracle.elapsed());
+    }
+
+    #[actix_web::test]
+    async fn test_noisy_leak() {
+        let model = LeakModel {
+            base: Duration::from_millis(1),
+            per_byte: Duration::from_micros(100),
+            jitter: Duration::from_micros(100),
+            outlier_probability: 0.02,
+            outlier_delay: Duration::from_millis(10),
+        };
+        for (seed, statistic) in [(1, Statistic::Median), (2, Statistic::TrimmedMean(0.2))] {
+            let mac = hmac_sha1(KEY, &[seed as u8]);
+            let oracle = SimulatedOracle::new(&mac, model, seed);
+            let mut attack = TimingAttack::new(20);
+            attack.statistic = statistic;
+            assert_eq!(attack.run(&oracle).await, Some(mac));
+            println!("Simulated time: {:?}", oracle.elapsed());
+        }
+    }
+}
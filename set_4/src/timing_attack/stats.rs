@@ -0,0 +1,84 @@
+//! Robust statistics for noisy timing samples.
+
+pub fn mean(samples: &[f64]) -> f64 {
+    samples.iter().sum::<f64>() / samples.len() as f64
+}
+
+/// Unbiased sample variance, 0 for less than 2 samples.
+pub fn variance(samples: &[f64]) -> f64 {
+    if samples.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(samples);
+    samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (samples.len() - 1) as f64
+}
+
+fn sorted(samples: &[f64]) -> Vec<f64> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted
+}
+
+/// NaN for no samples, like `mean`.
+pub fn median(samples: &[f64]) -> f64 {
+    if samples.is_empty() {
+        return f64::NAN;
+    }
+    let sorted = sorted(samples);
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+/// The sorted samples without the `fraction` smallest and the `fraction` largest ones. A
+/// hiccup only ever makes a request slower, but cutting both ends keeps the mean unbiased.
+/// At most half of the samples are cut from each end.
+pub fn trim(samples: &[f64], fraction: f64) -> Vec<f64> {
+    let sorted = sorted(samples);
+    let cut = ((sorted.len() as f64 * fraction) as usize).min(sorted.len() / 2);
+    sorted[cut..sorted.len() - cut].to_vec()
+}
+
+pub fn trimmed_mean(samples: &[f64], fraction: f64) -> f64 {
+    mean(&trim(samples, fraction))
+}
+
+/// Welch's t statistic for "the mean of `a` is larger than the mean of `b`", without assuming
+/// the same variance. Above 3 or 4 the difference is very unlikely to be noise.
+pub fn welch_t(a: &[f64], b: &[f64]) -> f64 {
+    let difference = mean(a) - mean(b);
+    let error = (variance(a) / a.len() as f64 + variance(b) / b.len() as f64).sqrt();
+    if error == 0.0 {
+        // No noise at all: any difference is significant.
+        return if difference > 0.0 { f64::INFINITY } else { 0.0 };
+    }
+    difference / error
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::timing_attack::stats::{median, trim, trimmed_mean, variance, welch_t};
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(median(&[3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), 2.5);
+        assert!(median(&[]).is_nan());
+        assert_eq!(
+            variance(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]),
+            32.0 / 7.0
+        );
+        let with_outliers = [1.0, 10.0, 10.0, 10.0, 10.0, 10.0, 10.0, 10.0, 10.0, 1000.0];
+        assert_eq!(trimmed_mean(&with_outliers, 0.1), 10.0);
+        assert_eq!(trim(&[3.0, 1.0, 2.0], 0.9), [2.0]);
+
+        let slow = [10.0, 11.0, 10.5, 10.2, 10.8];
+        let fast = [5.0, 5.5, 5.2, 4.9, 5.1];
+        assert!(welch_t(&slow, &fast) > 10.0);
+        assert!(welch_t(&fast, &slow) < 0.0);
+        assert!(welch_t(&slow, &slow).abs() < 1e-9);
+    }
+}